@@ -0,0 +1,302 @@
+use std::collections::BTreeMap;
+use num_bigint::{BigUint, RandBigInt};
+use rand::Rng;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ChainError {
+    GapTooLarge { from: u32, to: u32 },
+}
+
+pub struct AdapterChain {
+    joltages: Vec<u32>,
+    max_gap: u32,
+}
+
+impl AdapterChain {
+    pub fn new(adapters: &[u32], max_gap: u32, device_offset: u32) -> Result<Self, ChainError> {
+        let mut joltages = Vec::with_capacity(adapters.len() + 2);
+        joltages.push(0);
+        joltages.extend_from_slice(adapters);
+        joltages.sort_unstable();
+        joltages.push(joltages[joltages.len() - 1] + device_offset);
+
+        if let Some(gap) = joltages.windows(2).find(|pair| pair[1] - pair[0] > max_gap) {
+            return Err(ChainError::GapTooLarge { from: gap[0], to: gap[1] });
+        }
+
+        Ok(AdapterChain { joltages, max_gap })
+    }
+
+    pub fn differences(&self) -> BTreeMap<u32, usize> {
+        let mut counts = BTreeMap::new();
+        for pair in self.joltages.windows(2) {
+            *counts.entry(pair[1] - pair[0]).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    // ways[i] is the number of ways to get from the outlet to adapter i, which is the sum of the
+    // ways to reach every adapter within max_gap below it. Keeping a running sum of that window
+    // makes this linear even with lots of adapters at the same joltage.
+    fn ways_to_reach(&self) -> Vec<BigUint> {
+        let mut ways: Vec<BigUint> = Vec::with_capacity(self.joltages.len());
+        ways.push(BigUint::from(1u32));
+
+        let mut window_start = 0;
+        let mut window_sum = BigUint::from(1u32);
+        for i in 1..self.joltages.len() {
+            while self.joltages[i] - self.joltages[window_start] > self.max_gap {
+                window_sum -= &ways[window_start];
+                window_start += 1;
+            }
+
+            ways.push(window_sum.clone());
+            window_sum += &ways[i];
+        }
+
+        ways
+    }
+
+    pub fn count_arrangements(&self) -> BigUint {
+        self.ways_to_reach().pop().unwrap()
+    }
+
+    pub fn arrangements(&self) -> Arrangements<'_> {
+        let mut path = vec![0];
+        self.extend_with_every_adapter(&mut path);
+        Arrangements { chain: self, path: Some(path) }
+    }
+
+    fn extend_with_every_adapter(&self, path: &mut Vec<usize>) {
+        let last = path[path.len() - 1];
+        path.extend(last + 1..self.joltages.len());
+    }
+
+    fn adapters_in(&self, path: &[usize]) -> Vec<u32> {
+        path[1..path.len() - 1].iter().map(|i| self.joltages[*i]).collect()
+    }
+
+    pub fn maximal_chain(&self) -> Vec<u32> {
+        self.joltages[1..self.joltages.len() - 1].to_vec()
+    }
+
+    // always jumping to the highest adapter in reach never leaves us further behind than any
+    // other choice would, so it uses the fewest adapters
+    pub fn minimal_chain(&self) -> Vec<u32> {
+        let end = self.joltages.len() - 1;
+        let mut path = vec![0];
+        let mut i = 0;
+        while i < end {
+            let mut next = i + 1;
+            while next < end && self.joltages[next + 1] - self.joltages[i] <= self.max_gap {
+                next += 1;
+            }
+            path.push(next);
+            i = next;
+        }
+        self.adapters_in(&path)
+    }
+
+    // walks backwards from the device, picking each predecessor with probability proportional
+    // to the number of ways to reach it, which makes every complete arrangement equally likely
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Vec<u32> {
+        let ways = self.ways_to_reach();
+        let mut path = vec![self.joltages.len() - 1];
+        let mut i = self.joltages.len() - 1;
+        while i > 0 {
+            let mut pick = rng.gen_biguint_below(&ways[i]);
+            let mut k = i - 1;
+            while pick >= ways[k] {
+                pick -= &ways[k];
+                k -= 1;
+            }
+            path.push(k);
+            i = k;
+        }
+        path.reverse();
+        self.adapters_in(&path)
+    }
+}
+
+pub struct Arrangements<'a> {
+    chain: &'a AdapterChain,
+    path: Option<Vec<usize>>,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<u32>;
+
+    // arrangements come out in lexicographic order of the adapters they use: starting from
+    // the one that uses every adapter, each step swaps the last adapter that can be skipped
+    // for the next one up and then takes every adapter after it
+    fn next(&mut self) -> Option<Vec<u32>> {
+        let path = self.path.as_mut()?;
+        let arrangement = self.chain.adapters_in(path);
+
+        let joltages = &self.chain.joltages;
+        loop {
+            let skipped = path.pop().unwrap();
+            let prev = match path.last() {
+                Some(prev) => *prev,
+                None => {
+                    self.path = None;
+                    break;
+                }
+            };
+            if skipped + 1 < joltages.len() && joltages[skipped + 1] - joltages[prev] <= self.chain.max_gap {
+                path.push(skipped + 1);
+                self.chain.extend_with_every_adapter(path);
+                break;
+            }
+        }
+
+        Some(arrangement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::adapters::{AdapterChain, ChainError};
+
+    const SMALL_EXAMPLE: [u32; 11] = [16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4];
+    const LARGE_EXAMPLE: [u32; 31] = [
+        28, 33, 18, 42, 31, 14, 46, 20, 48, 47, 24, 23, 49, 45, 19, 38, 39, 11, 1, 32, 25, 35, 8, 17, 7, 9, 4, 2,
+        34, 10, 3,
+    ];
+
+    // tries every subset of the adapters, telling equal joltages apart by position
+    fn brute_force_count(adapters: &[u32], max_gap: u32, device_offset: u32) -> u64 {
+        let device = adapters.iter().max().unwrap() + device_offset;
+        (0..1u32 << adapters.len())
+            .filter(|subset| {
+                let mut joltages = (0..adapters.len())
+                    .filter(|i| subset & (1 << i) != 0)
+                    .map(|i| adapters[i])
+                    .collect::<Vec<u32>>();
+                joltages.push(0);
+                joltages.push(device);
+                joltages.sort_unstable();
+                joltages.windows(2).all(|pair| pair[1] - pair[0] <= max_gap)
+            })
+            .count() as u64
+    }
+
+    #[test]
+    fn counts_example_arrangements() {
+        let chain = AdapterChain::new(&SMALL_EXAMPLE, 3, 3).unwrap();
+        assert_eq!(chain.count_arrangements(), BigUint::from(8u32));
+        assert_eq!(chain.differences().into_iter().collect::<Vec<(u32, usize)>>(), vec![(1, 7), (3, 5)]);
+
+        let chain = AdapterChain::new(&LARGE_EXAMPLE, 3, 3).unwrap();
+        assert_eq!(chain.count_arrangements(), BigUint::from(19208u32));
+        assert_eq!(chain.differences().into_iter().collect::<Vec<(u32, usize)>>(), vec![(1, 22), (3, 10)]);
+    }
+
+    #[test]
+    fn ways_to_reach_each_adapter() {
+        let chain = AdapterChain::new(&[1, 2, 3, 4], 2, 1).unwrap();
+        let ways = chain.ways_to_reach().into_iter().map(|n| n.to_string()).collect::<Vec<String>>();
+        assert_eq!(ways, vec!["1", "1", "2", "3", "5", "8"]);
+    }
+
+    #[test]
+    fn duplicate_joltages_are_different_adapters() {
+        let chain = AdapterChain::new(&[1, 1], 3, 3).unwrap();
+        assert_eq!(chain.count_arrangements(), BigUint::from(3u32));
+
+        let chain = AdapterChain::new(&[2, 2, 2, 5], 3, 3).unwrap();
+        assert_eq!(chain.count_arrangements(), BigUint::from(brute_force_count(&[2, 2, 2, 5], 3, 3)));
+    }
+
+    #[test]
+    fn counts_match_brute_force() {
+        let inputs: [&[u32]; 4] = [&SMALL_EXAMPLE, &[1, 2, 3, 4, 5, 6, 7], &[3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5], &[2, 4, 6, 6, 8, 9]];
+        for adapters in inputs.iter() {
+            for max_gap in 1..=5 {
+                for device_offset in 1..=max_gap {
+                    let expected = brute_force_count(adapters, max_gap, device_offset);
+                    match AdapterChain::new(adapters, max_gap, device_offset) {
+                        Ok(chain) => assert_eq!(chain.count_arrangements(), BigUint::from(expected)),
+                        Err(_) => assert_eq!(expected, 0),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reports_gaps_that_cannot_be_bridged() {
+        assert_eq!(AdapterChain::new(&[1, 5], 3, 3).err(), Some(ChainError::GapTooLarge { from: 1, to: 5 }));
+        assert_eq!(AdapterChain::new(&[4, 5], 3, 3).err(), Some(ChainError::GapTooLarge { from: 0, to: 4 }));
+        assert_eq!(AdapterChain::new(&[1, 2], 3, 4).err(), Some(ChainError::GapTooLarge { from: 2, to: 6 }));
+        assert_eq!(AdapterChain::new(&SMALL_EXAMPLE, 2, 3).err(), Some(ChainError::GapTooLarge { from: 1, to: 4 }));
+        assert!(AdapterChain::new(&[1, 2], 4, 4).is_ok());
+    }
+
+    fn is_valid(chain: &AdapterChain, arrangement: &[u32]) -> bool {
+        let device = chain.joltages[chain.joltages.len() - 1];
+        let mut joltages = vec![0];
+        joltages.extend_from_slice(arrangement);
+        joltages.push(device);
+        joltages.windows(2).all(|pair| pair[0] <= pair[1] && pair[1] - pair[0] <= chain.max_gap)
+    }
+
+    #[test]
+    fn enumerates_every_arrangement_in_order() {
+        for adapters in [&SMALL_EXAMPLE[..], &LARGE_EXAMPLE[..], &[1, 2, 3, 5, 6, 8, 9, 11]] {
+            let chain = AdapterChain::new(adapters, 3, 3).unwrap();
+            let arrangements = chain.arrangements().collect::<Vec<Vec<u32>>>();
+
+            assert_eq!(BigUint::from(arrangements.len()), chain.count_arrangements());
+            assert!(arrangements.iter().all(|arrangement| is_valid(&chain, arrangement)));
+            // strictly increasing, so every one of them is different too
+            assert!(arrangements.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+
+        let chain = AdapterChain::new(&SMALL_EXAMPLE, 3, 3).unwrap();
+        assert_eq!(chain.arrangements().next(), Some(vec![1, 4, 5, 6, 7, 10, 11, 12, 15, 16, 19]));
+        assert_eq!(chain.arrangements().last(), Some(vec![1, 4, 7, 10, 12, 15, 16, 19]));
+    }
+
+    #[test]
+    fn finds_minimal_and_maximal_chains() {
+        let chain = AdapterChain::new(&SMALL_EXAMPLE, 3, 3).unwrap();
+        assert_eq!(chain.minimal_chain(), vec![1, 4, 7, 10, 12, 15, 16, 19]);
+        assert_eq!(chain.maximal_chain(), vec![1, 4, 5, 6, 7, 10, 11, 12, 15, 16, 19]);
+
+        for adapters in [&SMALL_EXAMPLE[..], &[1, 2, 3, 5, 6, 8, 9, 11]] {
+            for (max_gap, device_offset) in [(3, 3), (4, 1), (5, 5)] {
+                let chain = AdapterChain::new(adapters, max_gap, device_offset).unwrap();
+                let shortest = chain.arrangements().map(|arrangement| arrangement.len()).min().unwrap();
+                assert!(is_valid(&chain, &chain.minimal_chain()));
+                assert!(is_valid(&chain, &chain.maximal_chain()));
+                assert_eq!(chain.minimal_chain().len(), shortest);
+                assert_eq!(chain.maximal_chain().len(), adapters.len());
+            }
+        }
+    }
+
+    #[test]
+    fn samples_valid_arrangements() {
+        let chain = AdapterChain::new(&SMALL_EXAMPLE, 3, 3).unwrap();
+        let arrangements = chain.arrangements().collect::<Vec<Vec<u32>>>();
+        let mut seen = vec![0; arrangements.len()];
+
+        let mut rng = StdRng::seed_from_u64(2020);
+        for _ in 0..800 {
+            let sample = chain.sample(&mut rng);
+            assert!(is_valid(&chain, &sample));
+            seen[arrangements.iter().position(|arrangement| *arrangement == sample).unwrap()] += 1;
+        }
+        // each of the 8 arrangements should come up about 100 times
+        assert!(seen.iter().all(|count| (50..150).contains(count)), "{:?}", seen);
+
+        let chain = AdapterChain::new(&LARGE_EXAMPLE, 3, 3).unwrap();
+        for _ in 0..100 {
+            assert!(is_valid(&chain, &chain.sample(&mut rng)));
+        }
+    }
+}
@@ -1,7 +1,12 @@
+mod adapters;
+
 use std::env;
 use std::fs;
 use std::num::ParseIntError;
-use nom::lib::std::collections::HashMap;
+use itertools::Itertools;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use crate::adapters::{AdapterChain, ChainError};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -11,53 +16,66 @@ fn main() {
 
     let contents = fs::read_to_string(input_path)
         .expect("Something went wrong reading the file");
-    let mut nums = contents.lines().map(|s| s.parse::<u32>()).collect::<Result<Vec<u32>, ParseIntError>>()
+    let nums = contents.lines().map(|s| s.parse::<u32>()).collect::<Result<Vec<u32>, ParseIntError>>()
         .expect("Could not parse lines as numbers");
-    nums.sort();
-    nums.push(nums[nums.len() - 1] + 3);
-    nums.insert(0, 0);
 
-    part1(&nums);
-    part2(&nums);
-}
+    let option = |name: &str, default: u32| {
+        args[2..].iter()
+            .find_map(|arg| arg.strip_prefix(name))
+            .map(|n| n.parse::<u32>().expect("Could not parse option value"))
+            .unwrap_or(default)
+    };
+    let max_gap = option("--max-gap=", 3);
+    let device_offset = option("--device-offset=", 3);
 
-fn part1(nums: &Vec<u32>) {
-    let mut counts: HashMap<u32, u32> = HashMap::new();
-    for n in nums.iter().zip(&nums[1..]).map(|(a, b)| *b - *a) {
-        counts.insert(n, counts.get(&n).cloned().unwrap_or(0) + 1);
-    }
+    let chain = match AdapterChain::new(&nums, max_gap, device_offset) {
+        Ok(chain) => chain,
+        Err(ChainError::GapTooLarge { from, to }) => {
+            println!("The adapters can't be chained: nothing bridges the gap from {} to {} jolts", from, to);
+            return;
+        }
+    };
 
-    let result = *counts.get(&1).unwrap() * *counts.get(&3).unwrap();
-    println!("The product of the count of 1-diffs and 3-diffs is {}", result);
-}
+    part1(&chain);
+    part2(&chain);
 
-fn part2(nums: &Vec<u32>) {
-    let mut cache = HashMap::new();
-    println!("The adapters can be arranged {} different ways", count_arrangements(&nums[..], &mut cache));
+    if args[2..].iter().any(|arg| arg == "--chains") {
+        println!("Minimal chain: {}", format_chain(&chain.minimal_chain()));
+        println!("Maximal chain: {}", format_chain(&chain.maximal_chain()));
+    }
+    if let Some(limit) = args[2..].iter().find_map(|arg| arg.strip_prefix("--list=")) {
+        let limit = limit.parse::<usize>().expect("Could not parse option value");
+        for arrangement in chain.arrangements().take(limit) {
+            println!("{}", format_chain(&arrangement));
+        }
+    }
+    let samples = option("--sample=", 0);
+    if samples > 0 {
+        let mut rng = match args[2..].iter().find_map(|arg| arg.strip_prefix("--seed=")) {
+            Some(seed) => StdRng::seed_from_u64(seed.parse().expect("Could not parse option value")),
+            None => StdRng::from_entropy(),
+        };
+        for _ in 0..samples {
+            println!("{}", format_chain(&chain.sample(&mut rng)));
+        }
+    }
 }
 
-fn count_arrangements(nums: &[u32], cache: &mut HashMap<u32, u64>) -> u64 {
-    if nums.len() == 1 {
-        return 1;
+fn part1(chain: &AdapterChain) {
+    let counts = chain.differences();
+    for (diff, count) in &counts {
+        println!("There are {} differences of {} jolts", count, diff);
     }
 
-    let curr_num = &nums[0];
-    match cache.get(curr_num) {
-        Some(total) => *total,
-        _ => {
-            let mut total = 0;
-
-            let candidates = &nums[1..];
-            for (i, n) in candidates.iter().enumerate() {
-                if *n > curr_num + 3 {
-                    break;
-                }
+    let count = |diff| counts.get(&diff).cloned().unwrap_or(0);
+    let result = count(1) * count(3);
+    println!("The product of the count of 1-diffs and 3-diffs is {}", result);
+}
 
-                total += count_arrangements(&candidates[i..], cache);
-            }
+fn part2(chain: &AdapterChain) {
+    println!("The adapters can be arranged {} different ways", chain.count_arrangements());
+}
 
-            cache.insert(*curr_num, total);
-            total
-        }
-    }
+fn format_chain(adapters: &[u32]) -> String {
+    adapters.iter().join(", ")
 }
@@ -2,7 +2,7 @@ mod sim;
 
 use std::env;
 use std::fs;
-use crate::sim::Simulator;
+use crate::sim::{Neighbourhood, Outcome, Rules, Simulator};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -12,22 +12,59 @@ fn main() {
 
     let contents = fs::read_to_string(input_path)
         .expect("Something went wrong reading the file");
-    let simulator = Simulator::new(&contents);
 
-    part1(&simulator);
-    part2(&simulator);
+    let option = |name: &str| args[2..].iter().find_map(|arg| arg.strip_prefix(name));
+    let neighbourhood = option("--neighbourhood=")
+        .map(|s| s.parse::<Neighbourhood>().expect("Unknown neighbourhood, expected adjacent, line-of-sight or radius-N"));
+    let birth = option("--birth=").map(|n| n.parse::<usize>().expect("Could not parse birth threshold"));
+    let death = option("--death=").map(|n| n.parse::<usize>().expect("Could not parse death threshold"));
+
+    let show_stats = args[2..].iter().any(|arg| arg == "--stats");
+    let max_steps = option("--max-steps=").map_or(10_000, |n| n.parse::<usize>().expect("Could not parse max steps"));
+
+    if neighbourhood.is_none() && birth.is_none() && death.is_none() {
+        part1(&contents, max_steps, show_stats);
+        part2(&contents, max_steps, show_stats);
+        return;
+    }
+
+    let defaults = Rules::adjacent();
+    let rules = Rules {
+        neighbourhood: neighbourhood.unwrap_or(defaults.neighbourhood),
+        birth: birth.unwrap_or(defaults.birth),
+        death: death.unwrap_or(defaults.death),
+    };
+    println!("Simulating with {:?}", rules);
+    simulate(&contents, rules, max_steps, show_stats);
 }
 
-fn part1(sim: &Simulator) {
-    let mut sim = sim.clone();
-    sim.run_until_stable();
+fn simulate(contents: &str, rules: Rules, max_steps: usize, show_stats: bool) {
+    let mut sim = Simulator::new(contents, rules).with_step_limit(max_steps);
+    let outcome = sim.run_until_stable();
 
-    println!("The number of occupied seats when stable is {}", sim.num_occupied())
+    if show_stats {
+        for (step, stats) in sim.history().iter().enumerate() {
+            println!("Step {:>4}: {:>5} seats changed, {:>5} occupied", step + 1, stats.changed, stats.occupied);
+        }
+    }
+
+    match outcome {
+        Outcome::Stable { steps } => {
+            println!("The number of occupied seats when stable after {} steps is {}", steps, sim.num_occupied())
+        }
+        Outcome::Cycle { start, period } => {
+            println!("The seats never settle: from step {} they repeat every {} steps", start, period)
+        }
+        Outcome::StepLimitExceeded { steps } => {
+            println!("The seats were still changing after {} steps", steps)
+        }
+    }
 }
 
-fn part2(sim: &Simulator) {
-    let mut sim = sim.clone();
-    sim.run_until_stable2();
+fn part1(contents: &str, max_steps: usize, show_stats: bool) {
+    simulate(contents, Rules::adjacent(), max_steps, show_stats);
+}
 
-    println!("The number of occupied seats when stable is {}", sim.num_occupied())
+fn part2(contents: &str, max_steps: usize, show_stats: bool) {
+    simulate(contents, Rules::line_of_sight(), max_steps, show_stats);
 }
@@ -1,167 +1,385 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Neighbourhood {
+    Adjacent,
+    LineOfSight,
+    Radius(usize),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseNeighbourhoodError(String);
+
+impl FromStr for Neighbourhood {
+    type Err = ParseNeighbourhoodError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "adjacent" => Ok(Neighbourhood::Adjacent),
+            "line-of-sight" => Ok(Neighbourhood::LineOfSight),
+            _ => s.strip_prefix("radius-")
+                .and_then(|n| n.parse().ok())
+                .map(Neighbourhood::Radius)
+                .ok_or_else(|| ParseNeighbourhoodError(s.to_owned())),
+        }
+    }
+}
+
+// an empty seat fills up when at most `birth` of its neighbours are occupied, and an occupied
+// seat empties when at least `death` of them are
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Rules {
+    pub neighbourhood: Neighbourhood,
+    pub birth: usize,
+    pub death: usize,
+}
+
+impl Rules {
+    pub fn adjacent() -> Self {
+        Rules { neighbourhood: Neighbourhood::Adjacent, birth: 0, death: 4 }
+    }
+
+    pub fn line_of_sight() -> Self {
+        Rules { neighbourhood: Neighbourhood::LineOfSight, birth: 0, death: 5 }
+    }
+}
+
+const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct StepStats {
+    pub changed: usize,
+    pub occupied: usize,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Outcome {
+    Stable { steps: usize },
+    Cycle { start: usize, period: usize },
+    StepLimitExceeded { steps: usize },
+}
+
 #[derive(Clone)]
 pub struct Simulator {
     map: Vec<char>,
-    width: usize,
-    height: usize,
+    next: Vec<char>,
+    neighbors: Vec<Vec<usize>>,
+    rules: Rules,
+    step_limit: Option<usize>,
+    history: Vec<StepStats>,
 }
 
 impl Simulator {
-    pub fn new(s: &str) -> Self {
-        let width = s.lines().nth(0).unwrap().len();
-        let height = s.lines().count();
-        Simulator {
+    pub fn new(s: &str, rules: Rules) -> Self {
+        let layout = Layout {
             map: s.chars().filter(|c| *c != '\n').collect(),
-            width,
-            height,
+            width: s.lines().next().unwrap().len(),
+            height: s.lines().count(),
+        };
+        // floor never changes, so it's left out of the neighbor lists entirely
+        let neighbors = (0..layout.map.len())
+            .map(|i| match layout.map[i] {
+                '.' => Vec::new(),
+                _ => layout.neighbor_indices(i, rules.neighbourhood).into_iter()
+                    .filter(|j| layout.map[*j] != '.')
+                    .collect(),
+            })
+            .collect();
+
+        Simulator {
+            next: layout.map.clone(),
+            map: layout.map,
+            neighbors,
+            rules,
+            step_limit: None,
+            history: Vec::new(),
         }
     }
 
-    pub fn step(&mut self) -> usize {
-        let mut changes: Vec<(usize, char)> = Vec::new();
+    pub fn with_step_limit(mut self, limit: usize) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    // reads only from map and writes every cell of next, so the two can just be swapped
+    // afterwards instead of collecting a list of changes
+    pub fn step(&mut self) -> StepStats {
+        let mut stats = StepStats { changed: 0, occupied: 0 };
 
         for (i, c) in self.map.iter().enumerate() {
-            match *c {
-                'L' => {
-                    if self.count_occupied_neighbors(i) == 0 {
-                        changes.push((i, '#'));
-                    }
-                },
-                '#' => {
-                    if self.count_occupied_neighbors(i) >= 4 {
-                        changes.push((i, 'L'));
-                    }
-                },
-                _ => {},
-            }
-        }
+            let occupied_neighbors = || self.neighbors[i].iter().filter(|j| self.map[**j] == '#').count();
+            let next = match *c {
+                'L' if occupied_neighbors() <= self.rules.birth => '#',
+                '#' if occupied_neighbors() >= self.rules.death => 'L',
+                c => c,
+            };
 
-        for (i, c) in &changes {
-            self.map[*i] = *c;
+            if next != *c {
+                stats.changed += 1;
+            }
+            if next == '#' {
+                stats.occupied += 1;
+            }
+            self.next[i] = next;
         }
 
-        changes.len()
+        std::mem::swap(&mut self.map, &mut self.next);
+        self.history.push(stats);
+        stats
     }
 
-    pub fn run_until_stable(&mut self) {
+    // a layout that settles down is just a cycle with a period of one, so both are found by
+    // remembering the step at which each state was first seen. Only a hash of each state is
+    // looked up, and the packed states are compared to rule out collisions when one matches.
+    pub fn run_until_stable(&mut self) -> Outcome {
+        let first = self.history.len();
+        let mut states = vec![self.occupied_seats()];
+        let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+        seen.entry(hash_state(&states[0])).or_default().push(first);
+
         loop {
-            if self.step() == 0 {
-                return
+            if let Some(limit) = self.step_limit {
+                if self.history.len() >= limit {
+                    return Outcome::StepLimitExceeded { steps: self.history.len() };
+                }
             }
+
+            self.step();
+            let steps = self.history.len();
+            let state = self.occupied_seats();
+            let hash = hash_state(&state);
+
+            let earlier = seen.get(&hash)
+                .and_then(|candidates| candidates.iter().find(|step| states[**step - first] == state));
+            if let Some(start) = earlier {
+                return match steps - *start {
+                    1 => Outcome::Stable { steps: *start },
+                    period => Outcome::Cycle { start: *start, period },
+                };
+            }
+
+            seen.entry(hash).or_default().push(steps);
+            states.push(state);
         }
     }
 
-    pub fn step2(&mut self) -> usize {
-        let mut changes: Vec<(usize, char)> = Vec::new();
-
+    // floor and seats never swap, so which seats are occupied is the whole state, one bit a cell
+    fn occupied_seats(&self) -> Vec<u64> {
+        let mut bits = vec![0u64; self.map.len().div_ceil(64)];
         for (i, c) in self.map.iter().enumerate() {
-            match *c {
-                'L' => {
-                    if self.count_occupied_visible_seats(i) == 0 {
-                        changes.push((i, '#'));
-                    }
-                },
-                '#' => {
-                    if self.count_occupied_visible_seats(i) >= 5 {
-                        changes.push((i, 'L'));
-                    }
-                },
-                _ => {},
+            if *c == '#' {
+                bits[i / 64] |= 1 << (i % 64);
+            }
+        }
+        bits
+    }
+
+    pub fn history(&self) -> &[StepStats] {
+        &self.history
+    }
+
+    pub fn num_occupied(&self) -> usize {
+        self.map.iter().filter(|c| **c == '#').count()
+    }
+}
+
+fn hash_state(state: &[u64]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+struct Layout {
+    map: Vec<char>,
+    width: usize,
+    height: usize,
+}
+
+impl Layout {
+    fn neighbor_indices(&self, i: usize, neighbourhood: Neighbourhood) -> Vec<usize> {
+        match neighbourhood {
+            Neighbourhood::Adjacent => DIRECTIONS.iter()
+                .filter_map(|(dx, dy)| self.offset_index(i, *dx, *dy))
+                .collect(),
+            Neighbourhood::LineOfSight => DIRECTIONS.iter()
+                .filter_map(|(dx, dy)| self.visible_seat_index(i, *dx, *dy))
+                .collect(),
+            Neighbourhood::Radius(r) => {
+                let r = r as isize;
+                (-r..=r)
+                    .flat_map(|dy| (-r..=r).map(move |dx| (dx, dy)))
+                    .filter(|(dx, dy)| (*dx, *dy) != (0, 0))
+                    .filter_map(|(dx, dy)| self.offset_index(i, dx, dy))
+                    .collect()
             }
         }
+    }
 
-        for (i, c) in &changes {
-            self.map[*i] = *c;
+    fn offset_index(&self, i: usize, dx: isize, dy: isize) -> Option<usize> {
+        let x = (i % self.width) as isize + dx;
+        let y = (i / self.width) as isize + dy;
+
+        if x < 0 || y < 0 || x >= self.width as isize || y >= self.height as isize {
+            return None;
         }
 
-        changes.len()
+        Some(y as usize * self.width + x as usize)
     }
 
-    pub fn run_until_stable2(&mut self) {
+    fn visible_seat_index(&self, mut i: usize, dx: isize, dy: isize) -> Option<usize> {
         loop {
-            if self.step2() == 0 {
-                return
+            i = self.offset_index(i, dx, dy)?;
+            match self.map[i] {
+                '#' | 'L' => return Some(i),
+                '.' => {},
+                c => panic!("Unexpected character {}", c),
             }
         }
     }
+}
 
-    pub fn num_occupied(&self) -> usize {
-        self.map.iter().filter(|c| **c == '#').count()
+#[cfg(test)]
+mod tests {
+    use crate::sim::{Layout, Neighbourhood, Outcome, Rules, Simulator, StepStats};
+
+    const EXAMPLE: &str = "L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL";
+
+    fn layout(s: &str) -> Layout {
+        Layout {
+            map: s.chars().filter(|c| *c != '\n').collect(),
+            width: s.lines().next().unwrap().len(),
+            height: s.lines().count(),
+        }
     }
 
-    fn count_occupied_neighbors(&self, i: usize) -> usize {
-        self.neighbor_indices(i).iter().filter(|j| self.map[**j] == '#').count()
+    #[test]
+    fn example_settles() {
+        let mut sim = Simulator::new(EXAMPLE, Rules::adjacent());
+        assert_eq!(sim.run_until_stable(), Outcome::Stable { steps: 5 });
+        assert_eq!(sim.num_occupied(), 37);
+
+        let mut sim = Simulator::new(EXAMPLE, Rules::line_of_sight());
+        assert_eq!(sim.run_until_stable(), Outcome::Stable { steps: 6 });
+        assert_eq!(sim.num_occupied(), 26);
+
+        // a radius of one is the same as the adjacent neighbourhood
+        let rules = Rules { neighbourhood: Neighbourhood::Radius(1), ..Rules::adjacent() };
+        let mut sim = Simulator::new(EXAMPLE, rules);
+        assert_eq!(sim.run_until_stable(), Outcome::Stable { steps: 5 });
+        assert_eq!(sim.num_occupied(), 37);
     }
 
-    fn neighbor_indices(&self, i: usize) -> Vec<usize> {
-        vec![
-            (-1, -1),
-            (0, -1),
-            (1, -1),
-            (-1, 0),
-            (1, 0),
-            (-1, 1),
-            (0, 1),
-            (1, 1),
-        ].iter().filter_map(|(dx, dy)| self.adjacent_index(i, *dx, *dy)).collect()
+    #[test]
+    fn parses_neighbourhoods() {
+        assert_eq!("adjacent".parse(), Ok(Neighbourhood::Adjacent));
+        assert_eq!("line-of-sight".parse(), Ok(Neighbourhood::LineOfSight));
+        assert_eq!("radius-3".parse(), Ok(Neighbourhood::Radius(3)));
+        assert!("radius-".parse::<Neighbourhood>().is_err());
+        assert!("radius--1".parse::<Neighbourhood>().is_err());
+        assert!("diagonal".parse::<Neighbourhood>().is_err());
     }
 
-    fn count_occupied_visible_seats(&self, i: usize) -> usize {
-        self.visible_seat_indices(i).iter().filter(|j| self.map[**j] == '#').count()
+    #[test]
+    fn radius_neighbours() {
+        let layout = layout("LLLLL\nLLLLL\nLLLLL\nLLLLL\nLLLLL");
+        let centre = layout.neighbor_indices(12, Neighbourhood::Radius(2));
+        assert_eq!(centre, (0..25).filter(|i| *i != 12).collect::<Vec<usize>>());
+        assert_eq!(layout.neighbor_indices(0, Neighbourhood::Radius(2)), vec![1, 2, 5, 6, 7, 10, 11, 12]);
+        assert_eq!(layout.neighbor_indices(0, Neighbourhood::Radius(1)), vec![1, 5, 6]);
+        assert_eq!(layout.neighbor_indices(24, Neighbourhood::Radius(1)), vec![18, 19, 23]);
+        assert_eq!(layout.neighbor_indices(12, Neighbourhood::Radius(1)), layout.neighbor_indices(12, Neighbourhood::Adjacent));
+        assert!(layout.neighbor_indices(12, Neighbourhood::Radius(0)).is_empty());
     }
 
-    fn visible_seat_indices(&self, i: usize) -> Vec<usize> {
-        vec![
-            (-1, -1),
-            (0, -1),
-            (1, -1),
-            (-1, 0),
-            (1, 0),
-            (-1, 1),
-            (0, 1),
-            (1, 1),
-        ].iter().filter_map(|(dx, dy)| self.visible_seat_index(i, *dx, *dy)).collect()
+    #[test]
+    fn detects_stable_layouts() {
+        let mut sim = Simulator::new("L.LL\nLLLL\nL.L.", Rules::adjacent());
+        assert_eq!(sim.run_until_stable(), Outcome::Stable { steps: 2 });
+        assert_eq!(sim.history().len(), 3);
     }
 
-    fn adjacent_index(&self, i: usize, dx: i32, dy: i32) -> Option<usize> {
-        let x = i % self.width;
-        let y = i / self.width;
+    #[test]
+    fn detects_oscillating_layouts() {
+        let rules = Rules { neighbourhood: Neighbourhood::Adjacent, birth: 8, death: 1 };
+        let mut sim = Simulator::new("LL.\n...", rules);
+        assert_eq!(sim.run_until_stable(), Outcome::Cycle { start: 0, period: 2 });
 
-        let mut next_index = i;
-        if dx < 0 {
-            if (x as i32) + dx < 0 {
-                return None;
-            }
-            next_index -= dx.abs() as usize;
-        } else if dx > 0 {
-            if x + (dx as usize) >= self.width {
-                return None;
-            }
-            next_index += dx as usize;
-        }
+        let mut sim = Simulator::new("LL.\n...", rules).with_step_limit(1);
+        assert_eq!(sim.run_until_stable(), Outcome::StepLimitExceeded { steps: 1 });
+    }
 
-        if dy < 0 {
-            if (y as i32) + dy < 0 {
-                return None;
-            }
-            next_index -= (dy.abs() as usize) * self.width;
-        } else {
-            if y + (dy as usize) >= self.height {
-                return None;
-            }
-            next_index += (dy as usize) * self.width;
-        }
+    #[test]
+    fn records_step_stats() {
+        let stats = |steps: &[(usize, usize)]| steps.iter()
+            .map(|(changed, occupied)| StepStats { changed: *changed, occupied: *occupied })
+            .collect::<Vec<StepStats>>();
+
+        let mut sim = Simulator::new(EXAMPLE, Rules::adjacent());
+        sim.run_until_stable();
+        assert_eq!(sim.history(), &stats(&[(71, 71), (51, 20), (31, 51), (21, 30), (7, 37), (0, 37)])[..]);
 
-        Some(next_index)
+        let mut sim = Simulator::new(EXAMPLE, Rules::line_of_sight());
+        sim.run_until_stable();
+        assert_eq!(sim.history(), &stats(&[(71, 71), (64, 7), (46, 53), (35, 18), (13, 31), (5, 26), (0, 26)])[..]);
+
+        let mut sim = Simulator::new(EXAMPLE, Rules::adjacent());
+        assert_eq!(sim.step(), StepStats { changed: 71, occupied: 71 });
+        assert_eq!(sim.num_occupied(), 71);
     }
 
-    fn visible_seat_index(&self, i: usize, dx: i32, dy: i32) -> Option<usize> {
-        match self.adjacent_index(i, dx, dy) {
-            Some(j) => match self.map[j] {
-                '#' | 'L' => Some(j),
-                '.' => self.visible_seat_index(j, dx, dy),
-                c => panic!("Unexpected character {}", c),
-            },
-            None => None,
-        }
+    #[test]
+    fn line_of_sight_skips_floor() {
+        let row = layout(".............\n.L.L.#.#.#.#.\n.............");
+        assert_eq!(row.neighbor_indices(14, Neighbourhood::LineOfSight), vec![16]);
+        assert_eq!(row.neighbor_indices(20, Neighbourhood::LineOfSight), vec![18, 22]);
+
+        let surrounded = layout(".##.##.\n#.#.#.#\n##...##\n...L...\n##...##\n#.#.#.#\n.##.##.");
+        assert!(surrounded.neighbor_indices(24, Neighbourhood::LineOfSight).is_empty());
+    }
+
+    #[test]
+    fn neighbour_lists_leave_out_floor() {
+        let sim = Simulator::new("L.L\n...\nL#L", Rules::adjacent());
+        assert_eq!(sim.neighbors, vec![vec![], vec![], vec![], vec![], vec![], vec![], vec![7], vec![6, 8], vec![7]]);
+
+        let sim = Simulator::new("L.L\n...\nL#L", Rules::line_of_sight());
+        assert_eq!(sim.neighbors, vec![vec![2, 6, 8], vec![], vec![0, 6, 8], vec![], vec![], vec![], vec![0, 2, 7], vec![6, 8], vec![0, 2, 7]]);
+    }
+
+    #[test]
+    fn detects_cycles_in_wide_layouts() {
+        let rules = Rules { neighbourhood: Neighbourhood::Adjacent, birth: 8, death: 1 };
+        let row = "L".repeat(70);
+        let mut sim = Simulator::new(&format!("{}\n{}", row, ".".repeat(70)), rules);
+        assert_eq!(sim.run_until_stable(), Outcome::Cycle { start: 0, period: 2 });
+        assert_eq!(sim.occupied_seats().len(), 3);
+
+        // the cycle can start after some steps have already been taken
+        let mut sim = Simulator::new(EXAMPLE, Rules::adjacent());
+        sim.step();
+        sim.step();
+        assert_eq!(sim.run_until_stable(), Outcome::Stable { steps: 5 });
+        assert_eq!(sim.num_occupied(), 37);
     }
 }
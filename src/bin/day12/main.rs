@@ -1,8 +1,10 @@
 mod nav;
+mod plot;
 
 use std::env;
 use std::fs;
-use crate::nav::{Instruction, Navigator, WaypointNavigator};
+use crate::nav::{Instruction, Navigator, Ship, WaypointNavigator};
+use crate::plot::route_to_svg;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -12,22 +14,62 @@ fn main() {
 
     let contents = fs::read_to_string(input_path)
         .expect("Something went wrong reading the file");
-    let insts = Instruction::from_lines(&contents).unwrap();
+    let insts = match Instruction::from_lines(&contents) {
+        Ok(insts) => insts,
+        Err(err) => {
+            println!("Could not parse the instructions: {}", err);
+            return;
+        }
+    };
 
-    part1(&insts);
-    part2(&insts);
+    let plot = args[2..].iter().any(|arg| arg == "--plot");
+
+    part1(&insts, plot);
+    part2(&insts, plot);
+
+    if let Some(target) = args[2..].iter().find_map(|arg| arg.strip_prefix("--reach=")) {
+        let target = match target.split_once(',').map(|(x, y)| (x.parse::<i32>(), y.parse::<i32>())) {
+            Some((Ok(x), Ok(y))) => (x, y),
+            _ => panic!("Could not parse target, expected X,Y"),
+        };
+        reach(Navigator::new(), Navigator::instructions_to(target), target);
+        reach(WaypointNavigator::new(), WaypointNavigator::instructions_to(target), target);
+    }
+}
+
+fn reach<S: Ship>(mut ship: S, insts: Vec<Instruction>, target: (i32, i32)) {
+    ship.execute_all(&insts);
+    assert_eq!(ship.position(), target, "The instructions don't reach the target");
+
+    let listing = insts.iter().map(|inst| inst.to_string()).collect::<Vec<String>>().join(" ");
+    println!("{} instructions reach {:?}: {}", insts.len(), target, listing);
 }
 
-fn part1(insts: &Vec<Instruction>) {
+fn part1(insts: &[Instruction], plot: bool) {
     let mut nav = Navigator::new();
     nav.execute_all(insts);
 
     println!("The Manhattan distance of the ship from the start is {}", nav.distance_from_origin());
+    println!("The ship ends up at {:?} heading {:?}", nav.position(), nav.heading());
+    describe_route(&nav, "day12-part1.svg", plot);
 }
 
-fn part2(insts: &Vec<Instruction>) {
+fn part2(insts: &[Instruction], plot: bool) {
     let mut nav = WaypointNavigator::new();
     nav.execute_all(insts);
 
     println!("The Manhattan distance of the ship from the start is {}", nav.distance_from_origin());
-}
\ No newline at end of file
+    println!("The ship ends up at {:?} with the waypoint at {:?}", nav.position(), nav.waypoint());
+    describe_route(&nav, "day12-part2.svg", plot);
+}
+
+fn describe_route<S: Ship>(ship: &S, plot_path: &str, plot: bool) {
+    let bounds = ship.bounding_box();
+    println!("It sails {} units in total, within {:?} to {:?}", ship.distance_travelled(), bounds.min, bounds.max);
+
+    if plot {
+        fs::write(plot_path, route_to_svg(ship.path(), bounds))
+            .expect("Something went wrong writing the plot");
+        println!("Wrote the route to {}", plot_path);
+    }
+}
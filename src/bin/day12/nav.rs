@@ -1,12 +1,12 @@
 use nom::{IResult, Finish};
 use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::combinator::{value, map_res, map, all_consuming};
-use nom::character::complete::{digit1, newline};
-use nom::sequence::tuple;
-use nom::multi::separated_list1;
+use nom::character::complete::{char, digit1};
+use nom::combinator::{value, map_res, all_consuming, opt, recognize};
+use nom::sequence::pair;
+use std::fmt;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Direction {
     North,
     East,
@@ -14,7 +14,18 @@ pub enum Direction {
     West,
 }
 
-#[derive(Debug, Clone)]
+impl Direction {
+    fn turned_right(self) -> Direction {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Action {
     Direction(Direction),
     Left,
@@ -22,20 +33,77 @@ pub enum Action {
     Forward,
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct Instruction(Action, i32);
 
-impl Instruction {
-    pub fn from_lines(s: &str) -> Result<Vec<Instruction>, nom::error::Error<&str>> {
-        all_consuming(parse_instruction_lines)(s).finish().map(|(_, insts)| insts)
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseErrorKind {
+    UnknownAction(String),
+    BadValue(String),
+    UnsupportedAngle(i32),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ParseErrorKind::UnknownAction(action) => write!(f, "line {}: unknown action {:?}", self.line, action),
+            ParseErrorKind::BadValue(value) => write!(f, "line {}: {:?} is not a number", self.line, value),
+            ParseErrorKind::UnsupportedAngle(degrees) => {
+                write!(f, "line {}: can only turn in multiples of 90 degrees, not {}", self.line, degrees)
+            }
+        }
     }
 }
 
-fn parse_instruction_lines(s: &str) -> IResult<&str, Vec<Instruction>> {
-    separated_list1(newline, parse_instruction)(s)
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self.0 {
+            Action::Direction(Direction::North) => 'N',
+            Action::Direction(Direction::East) => 'E',
+            Action::Direction(Direction::South) => 'S',
+            Action::Direction(Direction::West) => 'W',
+            Action::Left => 'L',
+            Action::Right => 'R',
+            Action::Forward => 'F',
+        };
+        write!(f, "{}{}", action, self.1)
+    }
+}
+
+impl Instruction {
+    // turns are checked up front so that the ships only ever have to deal with whole quarter turns
+    fn new(action: Action, n: i32) -> Result<Instruction, ParseErrorKind> {
+        match action {
+            Action::Left | Action::Right if n % 90 != 0 => Err(ParseErrorKind::UnsupportedAngle(n)),
+            _ => Ok(Instruction(action, n)),
+        }
+    }
+
+    pub fn from_lines(s: &str) -> Result<Vec<Instruction>, ParseError> {
+        s.lines()
+            .enumerate()
+            .map(|(i, line)| parse_line(line).map_err(|kind| ParseError { line: i + 1, kind }))
+            .collect()
+    }
 }
 
-fn parse_instruction(s: &str) -> IResult<&str, Instruction> {
-    map(tuple((parse_action, parse_digit1)), |(a, n)| Instruction(a, n))(s)
+fn parse_line(line: &str) -> Result<Instruction, ParseErrorKind> {
+    let (rest, action) = parse_action(line)
+        .map_err(|_| ParseErrorKind::UnknownAction(line.chars().take(1).collect()))?;
+    // a negative turn is just a turn the other way, but a ship can't sail a negative distance
+    let parse_value: fn(&str) -> IResult<&str, i32> = match action {
+        Action::Left | Action::Right => parse_signed,
+        _ => parse_unsigned,
+    };
+    let (_, n) = all_consuming(parse_value)(rest).finish()
+        .map_err(|_: nom::error::Error<&str>| ParseErrorKind::BadValue(rest.to_owned()))?;
+    Instruction::new(action, n)
 }
 
 fn parse_action(s: &str) -> IResult<&str, Action> {
@@ -50,13 +118,59 @@ fn parse_action(s: &str) -> IResult<&str, Action> {
     ))(s)
 }
 
-fn parse_digit1(s: &str) -> IResult<&str, i32> {
+fn parse_unsigned(s: &str) -> IResult<&str, i32> {
     map_res(digit1, |s: &str| s.parse::<i32>())(s)
 }
 
+fn parse_signed(s: &str) -> IResult<&str, i32> {
+    map_res(recognize(pair(opt(char('-')), digit1)), |s: &str| s.parse::<i32>())(s)
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct BoundingBox {
+    pub min: (i32, i32),
+    pub max: (i32, i32),
+}
+
+pub trait Ship {
+    fn execute(&mut self, inst: &Instruction);
+
+    fn position(&self) -> (i32, i32);
+
+    fn path(&self) -> &[(i32, i32)];
+
+    fn execute_all(&mut self, insts: &[Instruction]) {
+        for inst in insts {
+            self.execute(inst);
+        }
+    }
+
+    fn distance_from_origin(&self) -> i32 {
+        let (x, y) = self.position();
+        x.abs() + y.abs()
+    }
+
+    // measured the same Manhattan way as the distance from the origin, so a ship that only
+    // ever sails away from the start travels exactly that far
+    fn distance_travelled(&self) -> i32 {
+        self.path().windows(2)
+            .map(|pair| (pair[1].0 - pair[0].0).abs() + (pair[1].1 - pair[0].1).abs())
+            .sum()
+    }
+
+    fn bounding_box(&self) -> BoundingBox {
+        let path = self.path();
+        BoundingBox {
+            min: (path.iter().map(|p| p.0).min().unwrap(), path.iter().map(|p| p.1).min().unwrap()),
+            max: (path.iter().map(|p| p.0).max().unwrap(), path.iter().map(|p| p.1).max().unwrap()),
+        }
+    }
+}
+
 pub struct Navigator {
     pos: (i32, i32),
     dir: Direction,
+    path: Vec<(i32, i32)>,
 }
 
 impl Navigator {
@@ -64,36 +178,64 @@ impl Navigator {
         Navigator {
             pos: (0, 0),
             dir: Direction::East,
+            path: vec![(0, 0)],
         }
     }
 
-    pub fn distance_from_origin(&self) -> i32 {
-        self.pos.0.abs() + self.pos.1.abs()
+    pub fn heading(&self) -> Direction {
+        self.dir
     }
 
-    pub fn execute_all(&mut self, insts: &Vec<Instruction>) {
-        for inst in insts {
-            self.execute(inst);
+    // moving the ship directly never depends on the heading, so one move per axis is all it takes
+    pub fn instructions_to(target: (i32, i32)) -> Vec<Instruction> {
+        let (x, y) = target;
+        let mut insts = Vec::new();
+        if x != 0 {
+            insts.push(move_along(x, Direction::East, Direction::West));
+        }
+        if y != 0 {
+            insts.push(move_along(y, Direction::North, Direction::South));
         }
+        insts
+    }
+}
+
+fn move_along(n: i32, positive: Direction, negative: Direction) -> Instruction {
+    if n > 0 {
+        Instruction(Action::Direction(positive), n)
+    } else {
+        Instruction(Action::Direction(negative), -n)
+    }
+}
+
+impl Ship for Navigator {
+    fn position(&self) -> (i32, i32) {
+        self.pos
+    }
+
+    fn path(&self) -> &[(i32, i32)] {
+        &self.path
     }
 
-    pub fn execute(&mut self, inst: &Instruction) {
+    fn execute(&mut self, inst: &Instruction) {
         match inst {
             Instruction(Action::Direction(dir), n) => {
                 self.execute_move(*dir, *n);
             }
             Instruction(Action::Left, degrees) => {
-                self.turn_left(*degrees);
+                self.turn(-degrees / 90);
             }
             Instruction(Action::Right, degrees) => {
-                self.turn_right(*degrees);
+                self.turn(degrees / 90);
             }
             Instruction(Action::Forward, n) => {
                 self.execute_move(self.dir, *n);
             }
         }
     }
+}
 
+impl Navigator {
     fn execute_move(&mut self, dir: Direction, n: i32) {
         let (x, y) = self.pos;
         self.pos = match dir {
@@ -102,42 +244,20 @@ impl Navigator {
             Direction::South => (x, y - n),
             Direction::West => (x - n, y),
         };
+        self.path.push(self.pos);
     }
 
-    fn turn_left(&mut self, degrees: i32) {
-        if degrees <= 0 {
-            return;
+    fn turn(&mut self, quarter_turns: i32) {
+        for _ in 0..quarter_turns.rem_euclid(4) {
+            self.dir = self.dir.turned_right();
         }
-
-        self.dir = match self.dir {
-            Direction::North => Direction::West,
-            Direction::East => Direction::North,
-            Direction::South => Direction::East,
-            Direction::West => Direction::South,
-        };
-
-        self.turn_left(degrees - 90);
-    }
-
-    fn turn_right(&mut self, degrees: i32) {
-        if degrees <= 0 {
-            return;
-        }
-
-        self.dir = match self.dir {
-            Direction::North => Direction::East,
-            Direction::East => Direction::South,
-            Direction::South => Direction::West,
-            Direction::West => Direction::North,
-        };
-
-        self.turn_right(degrees - 90);
     }
 }
 
 pub struct WaypointNavigator {
     pos: (i32, i32),
     offset: (i32, i32),
+    path: Vec<(i32, i32)>,
 }
 
 impl WaypointNavigator {
@@ -145,36 +265,94 @@ impl WaypointNavigator {
         WaypointNavigator {
             pos: (0, 0),
             offset: (10, 1),
+            path: vec![(0, 0)],
         }
     }
 
-    pub fn distance_from_origin(&self) -> i32 {
-        self.pos.0.abs() + self.pos.1.abs()
+    pub fn waypoint(&self) -> (i32, i32) {
+        self.offset
     }
 
-    pub fn execute_all(&mut self, insts: &Vec<Instruction>) {
-        for inst in insts {
-            self.execute(inst);
+    // the ship only moves by whole, non-negative multiples of the waypoint, so the last
+    // instruction is always a forward. moving the waypoint onto the target and going forward
+    // once always works in at most three instructions, so it's enough to look for a single
+    // forward or one instruction that sets up a waypoint the target is a multiple of.
+    pub fn instructions_to(target: (i32, i32)) -> Vec<Instruction> {
+        let (x, y) = target;
+        let start = WaypointNavigator::new().offset;
+        let forward = |n| Instruction(Action::Forward, n);
+
+        if target == (0, 0) {
+            return vec![];
+        }
+        if let Some(n) = multiple_of(target, start) {
+            return vec![forward(n)];
+        }
+
+        let mut waypoint = start;
+        for quarter_turns in 1..4 {
+            waypoint = (waypoint.1, -waypoint.0);
+            if let Some(n) = multiple_of(target, waypoint) {
+                return vec![Instruction(Action::Right, quarter_turns * 90), forward(n)];
+            }
+        }
+
+        // with the waypoint's y left alone, going forward y times is the only way to reach the target
+        if y > 0 && x % y == 0 {
+            return vec![move_along(x / y - start.0, Direction::East, Direction::West), forward(y)];
         }
+        if x > 0 && x % start.0 == 0 {
+            let n = x / start.0;
+            if y % n == 0 {
+                return vec![move_along(y / n - start.1, Direction::North, Direction::South), forward(n)];
+            }
+        }
+
+        let mut insts = Vec::new();
+        if x != start.0 {
+            insts.push(move_along(x - start.0, Direction::East, Direction::West));
+        }
+        if y != start.1 {
+            insts.push(move_along(y - start.1, Direction::North, Direction::South));
+        }
+        insts.push(forward(1));
+        insts
     }
+}
+
+fn multiple_of(target: (i32, i32), step: (i32, i32)) -> Option<i32> {
+    let n = if step.0 != 0 { target.0 / step.0 } else { target.1 / step.1 };
+    if n > 0 && (n * step.0, n * step.1) == target { Some(n) } else { None }
+}
 
-    pub fn execute(&mut self, inst: &Instruction) {
+impl Ship for WaypointNavigator {
+    fn position(&self) -> (i32, i32) {
+        self.pos
+    }
+
+    fn path(&self) -> &[(i32, i32)] {
+        &self.path
+    }
+
+    fn execute(&mut self, inst: &Instruction) {
         match inst {
             Instruction(Action::Direction(dir), n) => {
                 self.move_waypoint(*dir, *n);
             }
             Instruction(Action::Left, degrees) => {
-                self.turn_left(*degrees);
+                self.turn(-degrees / 90);
             }
             Instruction(Action::Right, degrees) => {
-                self.turn_right(*degrees);
+                self.turn(degrees / 90);
             }
             Instruction(Action::Forward, n) => {
                 self.move_to_waypoint(*n);
             }
         }
     }
+}
 
+impl WaypointNavigator {
     fn move_waypoint(&mut self, dir: Direction, n: i32) {
         let (x, y) = self.offset;
         self.offset = match dir {
@@ -185,31 +363,157 @@ impl WaypointNavigator {
         }
     }
 
-    fn turn_left(&mut self, degrees: i32) {
-        if degrees <= 0 {
-            return;
+    fn turn(&mut self, quarter_turns: i32) {
+        for _ in 0..quarter_turns.rem_euclid(4) {
+            let (x, y) = self.offset;
+            self.offset = (y, -x);
         }
+    }
 
-        let (x, y) = self.offset;
-        self.offset = (-y, x);
+    fn move_to_waypoint(&mut self, times: i32) {
+        let (x, y) = self.pos;
+        let (dx, dy) = self.offset;
+        self.pos = (x + (times * dx), y + (times * dy));
+        self.path.push(self.pos);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::nav::{BoundingBox, Direction, Instruction, Navigator, ParseError, ParseErrorKind, Ship, WaypointNavigator};
+
+    const EXAMPLE: &str = "F10\nN3\nF7\nR90\nF11";
+
+    #[test]
+    fn parse_errors_report_line_numbers() {
+        let err = |s| Instruction::from_lines(s).err();
+        assert_eq!(err("F10\nN3\nX7"), Some(ParseError { line: 3, kind: ParseErrorKind::UnknownAction("X".to_owned()) }));
+        assert_eq!(err("F10\nNorth"), Some(ParseError { line: 2, kind: ParseErrorKind::BadValue("orth".to_owned()) }));
+        assert_eq!(err("R45\nF10"), Some(ParseError { line: 1, kind: ParseErrorKind::UnsupportedAngle(45) }));
+    }
 
-        self.turn_left(degrees - 90);
+    #[test]
+    fn only_turns_can_be_negative() {
+        let err = |s| Instruction::from_lines(s).err();
+        assert_eq!(err("F-10"), Some(ParseError { line: 1, kind: ParseErrorKind::BadValue("-10".to_owned()) }));
+        assert_eq!(err("F10\nN-3"), Some(ParseError { line: 2, kind: ParseErrorKind::BadValue("-3".to_owned()) }));
+        for line in ["E-2", "S-1", "W-7"] {
+            assert_eq!(err(line), Some(ParseError { line: 1, kind: ParseErrorKind::BadValue(line[1..].to_owned()) }));
+        }
+        assert!(Instruction::from_lines("L-90\nR-180\nF0").is_ok());
     }
 
-    fn turn_right(&mut self, degrees: i32) {
-        if degrees <= 0 {
-            return;
+    #[test]
+    fn negative_turns_go_the_other_way() {
+        let insts = Instruction::from_lines("L-90\nF10\nR-270\nF5").unwrap();
+
+        let mut nav = Navigator::new();
+        nav.execute_all(&insts);
+        assert_eq!(nav.position(), (-5, -10));
+
+        let mut nav = WaypointNavigator::new();
+        nav.execute_all(&insts);
+        assert_eq!(nav.position(), (-40, -105));
+    }
+
+    #[test]
+    fn instructions_reach_their_target() {
+        for x in -25..=25 {
+            for y in -25..=25 {
+                let mut nav = Navigator::new();
+                nav.execute_all(&Navigator::instructions_to((x, y)));
+                assert_eq!(nav.position(), (x, y));
+
+                let mut nav = WaypointNavigator::new();
+                nav.execute_all(&WaypointNavigator::instructions_to((x, y)));
+                assert_eq!(nav.position(), (x, y));
+            }
         }
 
-        let (x, y) = self.offset;
-        self.offset = (y, -x);
+        let lengths = |target| (Navigator::instructions_to(target).len(), WaypointNavigator::instructions_to(target).len());
+        assert_eq!(lengths((0, 0)), (0, 0));
+        assert_eq!(lengths((30, 3)), (2, 1));
+        assert_eq!(lengths((-3, 30)), (2, 2));
+        assert_eq!(lengths((14, 2)), (2, 2));
+        assert_eq!(lengths((20, 6)), (2, 2));
+        assert_eq!(lengths((7, 0)), (1, 3));
+        assert_eq!(lengths((7, 5)), (2, 3));
+        assert_eq!(lengths((-10, -1)), (2, 2));
+        assert_eq!(WaypointNavigator::instructions_to((-10, -1)), Instruction::from_lines("R180\nF1").unwrap());
+    }
+
+    // forward values can't be negative, so no single instruction reaches (-10, -1) and nothing
+    // shorter than what instructions_to gives reaches any of these targets either
+    #[test]
+    fn waypoint_instructions_are_minimal() {
+        let mut insts = Instruction::from_lines("L90\nL180\nL270\nR90\nR180\nR270").unwrap();
+        for n in 0..=40 {
+            let line = ["N", "E", "S", "W", "F"].iter().map(|action| format!("{}{}", action, n)).collect::<Vec<String>>();
+            insts.extend(Instruction::from_lines(&line.join("\n")).unwrap());
+        }
 
-        self.turn_right(degrees - 90);
+        let reached_by = |program: &[Instruction]| {
+            let mut nav = WaypointNavigator::new();
+            nav.execute_all(program);
+            nav.position()
+        };
+        let one = insts.iter().map(|inst| reached_by(&[*inst])).collect::<HashSet<(i32, i32)>>();
+        let two = insts.iter()
+            .flat_map(|first| insts.iter().map(move |second| [*first, *second]))
+            .map(|program| reached_by(&program))
+            .collect::<HashSet<(i32, i32)>>();
+
+        for x in -12..=12 {
+            for y in -12..=12 {
+                let shortest = if (x, y) == (0, 0) {
+                    0
+                } else if one.contains(&(x, y)) {
+                    1
+                } else if two.contains(&(x, y)) {
+                    2
+                } else {
+                    3
+                };
+                assert_eq!(WaypointNavigator::instructions_to((x, y)).len(), shortest, "reaching {:?}", (x, y));
+            }
+        }
     }
 
-    fn move_to_waypoint(&mut self, times: i32) {
-        let (x, y) = self.pos;
-        let (dx, dy) = self.offset;
-        self.pos = (x + (times * dx), y + (times * dy));
+    #[test]
+    fn navigates_example() {
+        let mut nav = Navigator::new();
+        nav.execute_all(&Instruction::from_lines(EXAMPLE).unwrap());
+        assert_eq!(nav.position(), (17, -8));
+        assert_eq!(nav.heading(), Direction::South);
+        assert_eq!(nav.distance_from_origin(), 25);
+        assert_eq!(nav.path(), &[(0, 0), (10, 0), (10, 3), (17, 3), (17, -8)]);
+        assert_eq!(nav.bounding_box(), BoundingBox { min: (0, -8), max: (17, 3) });
+        assert_eq!(nav.distance_travelled(), 31);
+    }
+
+    #[test]
+    fn navigates_example_by_waypoint() {
+        let mut nav = WaypointNavigator::new();
+        nav.execute_all(&Instruction::from_lines(EXAMPLE).unwrap());
+        assert_eq!(nav.position(), (214, -72));
+        assert_eq!(nav.waypoint(), (4, -10));
+        assert_eq!(nav.distance_from_origin(), 286);
+        // moving the waypoint doesn't move the ship, so only forwards show up in the path
+        assert_eq!(nav.path(), &[(0, 0), (100, 10), (170, 38), (214, -72)]);
+        assert_eq!(nav.bounding_box(), BoundingBox { min: (0, -72), max: (214, 38) });
+        assert_eq!(nav.distance_travelled(), 110 + 98 + 154);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn ships_start_at_the_origin() {
+        let nav = Navigator::new();
+        assert_eq!(nav.path(), &[(0, 0)]);
+        assert_eq!(nav.bounding_box(), BoundingBox { min: (0, 0), max: (0, 0) });
+        assert_eq!(nav.distance_travelled(), 0);
+
+        let mut nav = Navigator::new();
+        nav.execute_all(&Instruction::from_lines("E5\nW5").unwrap());
+        assert_eq!(nav.distance_from_origin(), 0);
+        assert_eq!(nav.distance_travelled(), 10);
+    }
+}
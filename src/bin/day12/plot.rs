@@ -0,0 +1,58 @@
+use crate::nav::BoundingBox;
+
+const MARGIN: i32 = 10;
+
+// svg's y axis points down, so north is flipped to keep it at the top of the picture
+pub fn route_to_svg(path: &[(i32, i32)], bounds: BoundingBox) -> String {
+    let width = bounds.max.0 - bounds.min.0 + 2 * MARGIN;
+    let height = bounds.max.1 - bounds.min.1 + 2 * MARGIN;
+    let stroke = (width.max(height) / 500).max(1);
+
+    let points = path.iter()
+        .map(|(x, y)| format!("{},{}", x, -y))
+        .collect::<Vec<String>>()
+        .join(" ");
+    let (start, end) = (path[0], path[path.len() - 1]);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+        bounds.min.0 - MARGIN, -bounds.max.1 - MARGIN, width, height,
+    );
+    svg.push_str(&format!(
+        "  <polyline points=\"{}\" fill=\"none\" stroke=\"blue\" stroke-width=\"{}\"/>\n",
+        points, stroke,
+    ));
+    svg.push_str(&format!("  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"green\"/>\n", start.0, -start.1, stroke * 3));
+    svg.push_str(&format!("  <circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"red\"/>\n", end.0, -end.1, stroke * 3));
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nav::{Instruction, Navigator, Ship};
+    use crate::plot::route_to_svg;
+
+    #[test]
+    fn plots_example_route() {
+        let mut nav = Navigator::new();
+        nav.execute_all(&Instruction::from_lines("F10\nN3\nF7\nR90\nF11").unwrap());
+        assert_eq!(route_to_svg(nav.path(), nav.bounding_box()), concat!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"-10 -13 37 31\">\n",
+            "  <polyline points=\"0,0 10,0 10,-3 17,-3 17,8\" fill=\"none\" stroke=\"blue\" stroke-width=\"1\"/>\n",
+            "  <circle cx=\"0\" cy=\"0\" r=\"3\" fill=\"green\"/>\n",
+            "  <circle cx=\"17\" cy=\"8\" r=\"3\" fill=\"red\"/>\n",
+            "</svg>\n",
+        ));
+    }
+
+    #[test]
+    fn strokes_scale_with_the_route() {
+        let mut nav = Navigator::new();
+        nav.execute_all(&Instruction::from_lines("F2000\nS1000").unwrap());
+        let svg = route_to_svg(nav.path(), nav.bounding_box());
+        assert!(svg.contains("viewBox=\"-10 -10 2020 1020\""));
+        assert!(svg.contains("stroke-width=\"4\""));
+        assert!(svg.contains("<circle cx=\"2000\" cy=\"1000\" r=\"12\" fill=\"red\"/>"));
+    }
+}
@@ -1,6 +1,7 @@
 use std::env;
 use std::fs;
 use std::str::FromStr;
+use std::collections::HashSet;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -15,6 +16,12 @@ fn main() {
 
     part1(&map);
     part2(&map);
+
+    if args[2..].iter().any(|arg| arg == "--render") {
+        fs::write("day3-path.txt", map.render_path(1, 3)).unwrap();
+        map.render_path_bmp(1, 3).save("day3.bmp").unwrap();
+        println!("Wrote the path to day3-path.txt and day3.bmp");
+    }
 }
 
 fn part1(map: &Map) {
@@ -51,6 +58,13 @@ impl FromStr for MapItem {
     }
 }
 
+enum PathCell {
+    Open,
+    Tree,
+    OpenOnPath,
+    TreeOnPath,
+}
+
 struct Map {
     width: usize,
     height: usize,
@@ -87,26 +101,114 @@ impl Map {
     }
 
     fn trees_along_slope(&self, down: usize, right: usize) -> usize {
-        let mut num_trees = 0;
-
-        let mut row: usize = down;
-        let mut col: usize = right;
-
-        loop {
-            match self.get(row, col) {
-                Some(MapItem::Tree) => {
-                    num_trees += 1;
-                },
-                Some(MapItem::Empty) => {},
-                None => {
-                    break;
-                }
-            };
-
-            row += down;
-            col += right;
+        self.path_along_slope(down, right)
+            .filter(|(row, col)| matches!(self.get(*row, *col), Some(MapItem::Tree)))
+            .count()
+    }
+
+    fn path_along_slope(&self, down: usize, right: usize) -> impl Iterator<Item = (usize, usize)> {
+        let height = self.height;
+        (1..)
+            .map(move |step| (step * down, step * right))
+            .take_while(move |(row, _)| *row < height)
+    }
+
+    // the path can run off the right edge of the map many times over, so we repeat the map
+    // horizontally as many times as needed to show the whole path without wrapping
+    fn rendered_width(&self, down: usize, right: usize) -> usize {
+        let max_col = self.path_along_slope(down, right)
+            .map(|(_, col)| col)
+            .max()
+            .unwrap_or(0);
+        (max_col / self.width + 1) * self.width
+    }
+
+    fn path_cells(&self, down: usize, right: usize) -> Vec<Vec<PathCell>> {
+        let width = self.rendered_width(down, right);
+        let path: HashSet<(usize, usize)> = self.path_along_slope(down, right).collect();
+
+        (0..self.height)
+            .map(|row| {
+                (0..width)
+                    .map(|col| match (self.get(row, col), path.contains(&(row, col))) {
+                        (Some(MapItem::Tree), true) => PathCell::TreeOnPath,
+                        (Some(MapItem::Tree), false) => PathCell::Tree,
+                        (_, true) => PathCell::OpenOnPath,
+                        (_, false) => PathCell::Open,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn render_path(&self, down: usize, right: usize) -> String {
+        let mut s = String::new();
+        for row in self.path_cells(down, right) {
+            for cell in row {
+                s.push(match cell {
+                    PathCell::TreeOnPath => 'X',
+                    PathCell::Tree => '#',
+                    PathCell::OpenOnPath => 'O',
+                    PathCell::Open => '.',
+                });
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    fn render_path_bmp(&self, down: usize, right: usize) -> bmp::Image {
+        let cells = self.path_cells(down, right);
+
+        let mut img = bmp::Image::new(cells[0].len() as u32, cells.len() as u32);
+        for (row, cells) in cells.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                let color = match cell {
+                    PathCell::TreeOnPath => bmp::consts::RED,
+                    PathCell::Tree => bmp::consts::GREEN,
+                    PathCell::OpenOnPath => bmp::consts::BLUE,
+                    PathCell::Open => bmp::consts::WHITE,
+                };
+                img.set_pixel(col as u32, row as u32, color);
+            }
         }
+        img
+    }
+}
 
-        num_trees
+#[cfg(test)]
+mod tests {
+    use crate::Map;
+
+    const EXAMPLE: &str = "\
+..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+    #[test]
+    fn renders_example_path() {
+        let map = EXAMPLE.parse::<Map>().unwrap();
+        assert_eq!(map.trees_along_slope(1, 3), 7);
+        assert_eq!(map.render_path(1, 3), "\
+..##.........##.........##.......
+#..O#...#..#...#...#..#...#...#..
+.#....X..#..#....#..#..#....#..#.
+..#.#...#O#..#.#...#.#..#.#...#.#
+.#...##..#..X...##..#..#...##..#.
+..#.##.......#.X#.......#.##.....
+.#.#.#....#.#.#.#.O..#.#.#.#....#
+.#........#.#........X.#........#
+#.##...#...#.##...#...#.X#...#...
+#...##....##...##....##...#X....#
+.#..#...#.#.#..#...#.#.#..#...X.#
+");
     }
-}
\ No newline at end of file
+}
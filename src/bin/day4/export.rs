@@ -0,0 +1,111 @@
+use std::io::{self, Write};
+use crate::validated::ValidatedPassport;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    pub fn from_path(path: &str) -> Option<Format> {
+        if path.ends_with(".csv") {
+            Some(Format::Csv)
+        } else if path.ends_with(".json") {
+            Some(Format::Json)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn export<W: Write>(passports: &[ValidatedPassport], format: Format, mut writer: W) -> io::Result<()> {
+    match format {
+        Format::Csv => write_csv(passports, &mut writer),
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut writer, passports)?;
+            writeln!(writer)
+        }
+    }
+}
+
+fn write_csv<W: Write>(passports: &[ValidatedPassport], writer: &mut W) -> io::Result<()> {
+    writeln!(writer, "byr,iyr,eyr,hgt,hcl,ecl,pid,cid")?;
+    for p in passports {
+        let fields = [
+            p.birth_year.to_string(),
+            p.issue_year.to_string(),
+            p.expiration_year.to_string(),
+            p.height.to_string(),
+            p.hair_color.to_string(),
+            p.eye_color.to_string(),
+            p.passport_id.clone(),
+            p.country_id.clone().unwrap_or_default(),
+        ];
+        let row = fields.iter().map(|field| csv_field(field)).collect::<Vec<String>>();
+        writeln!(writer, "{}", row.join(","))?;
+    }
+    Ok(())
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export::{export, Format};
+    use crate::validated::{EyeColor, Height, Rgb, ValidatedPassport};
+
+    fn passports() -> Vec<ValidatedPassport> {
+        let passport = ValidatedPassport {
+            birth_year: 1980,
+            issue_year: 2012,
+            expiration_year: 2030,
+            height: Height::In(74),
+            hair_color: Rgb { r: 0x62, g: 0x3a, b: 0x2f },
+            eye_color: EyeColor::Green,
+            passport_id: "087499704".to_owned(),
+            country_id: None,
+        };
+        let with_cid = ValidatedPassport { country_id: Some("a, \"b\"".to_owned()), ..passport.clone() };
+        vec![passport, with_cid]
+    }
+
+    fn exported(format: Format) -> String {
+        let mut output = Vec::new();
+        export(&passports(), format, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn exports_csv() {
+        assert_eq!(exported(Format::Csv), "\
+byr,iyr,eyr,hgt,hcl,ecl,pid,cid
+1980,2012,2030,74in,#623a2f,grn,087499704,
+1980,2012,2030,74in,#623a2f,grn,087499704,\"a, \"\"b\"\"\"
+");
+    }
+
+    #[test]
+    fn exports_json() {
+        let json: serde_json::Value = serde_json::from_str(&exported(Format::Json)).unwrap();
+        assert_eq!(json[0], serde_json::json!({
+            "birth_year": 1980,
+            "issue_year": 2012,
+            "expiration_year": 2030,
+            "height": { "unit": "in", "value": 74 },
+            "hair_color": { "r": 98, "g": 58, "b": 47 },
+            "eye_color": "green",
+            "passport_id": "087499704",
+            "country_id": null,
+        }));
+        assert_eq!(json[1]["country_id"], "a, \"b\"");
+        assert_eq!(Format::from_path("out.csv"), Some(Format::Csv));
+        assert_eq!(Format::from_path("out.txt"), None);
+    }
+}
@@ -1,7 +1,18 @@
+mod export;
+mod passport;
+mod report;
+mod schema;
+mod validated;
+
 use std::env;
 use std::fs;
-use std::collections::HashMap;
+use std::io;
 use std::convert::TryFrom;
+use crate::export::{export, Format};
+use crate::passport::{Passport, ParseError};
+use crate::report::Summary;
+use crate::schema::Schema;
+use crate::validated::ValidatedPassport;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -16,12 +27,40 @@ fn main() {
         .collect::<Result<Vec<Passport>, ParseError>>()
         .expect("Could not parse list of passports");
 
-    part1(&passports);
-    part2(&passports);
+    let options = &args[2..];
+    let schema = match options.iter().find(|arg| !arg.starts_with("--")) {
+        Some(schema_path) => load_schema(schema_path),
+        None => Schema::default(),
+    };
+
+    part1(&passports, &schema);
+    part2(&passports, &schema);
+
+    if options.iter().any(|arg| arg == "--report") {
+        report(&passports, &schema);
+    }
+    if options.iter().any(|arg| arg == "--summary") {
+        print!("{}", Summary::new(&passports, &schema));
+    }
+    if let Some(export_path) = options.iter().find_map(|arg| arg.strip_prefix("--export=")) {
+        export_passports(&passports, &schema, export_path);
+    }
 }
 
-fn part1(passports: &Vec<Passport>) {
-    let required_fields = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
+fn load_schema(schema_path: &str) -> Schema {
+    println!("Reading validation schema from {}", schema_path);
+
+    let contents = fs::read_to_string(schema_path)
+        .expect("Something went wrong reading the schema file");
+    if schema_path.ends_with(".json") {
+        Schema::from_json(&contents).expect("Could not parse JSON schema")
+    } else {
+        Schema::from_toml(&contents).expect("Could not parse TOML schema")
+    }
+}
+
+fn part1(passports: &Vec<Passport>, schema: &Schema) {
+    let required_fields = schema.required_fields();
     let valid_count = passports.iter()
         .filter(|p| p.contains_fields(&required_fields))
         .count();
@@ -29,129 +68,51 @@ fn part1(passports: &Vec<Passport>) {
     println!("The number of passports with the required fields is {}", valid_count);
 }
 
-fn part2(passports: &Vec<Passport>) {
+fn part2(passports: &Vec<Passport>, schema: &Schema) {
     let valid_count = passports.iter()
-        .filter(|p| p.is_valid())
+        .filter(|p| p.is_valid(schema))
         .count();
 
     println!("The number of valid passports is {}", valid_count);
 }
 
-#[derive(Debug, Clone)]
-struct ParseError;
-
-struct Passport<'a> {
-    fields: HashMap<&'a str, &'a str>,
-}
-
-impl<'a> TryFrom<&'a str> for Passport<'a> {
-    type Error = ParseError;
-
-    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
-        let fields = s.split(|c| c == ' ' || c == '\n').map(|field_str| {
-            let v = field_str.split(':').collect::<Vec<&str>>();
-            if v.len() != 2 {
-                Err(ParseError)
-            } else {
-                Ok((v[0], v[1]))
-            }
-        }).collect::<Result<HashMap<&str, &str>, ParseError>>()?;
-
-        Ok(Passport { fields })
-    }
-}
+fn export_passports(passports: &[Passport], schema: &Schema, export_path: &str) {
+    let format = Format::from_path(export_path)
+        .expect("Export path must end in .csv or .json");
 
-impl<'a> Passport<'a> {
-    fn contains_fields(&self, fields: &[&str]) -> bool {
-        fields.iter().all(|field| self.fields.contains_key(field))
-    }
-
-    fn is_valid(&self) -> bool {
-        self.has_valid_birth_year() &&
-            self.has_valid_issue_year() &&
-            self.has_valid_exp_year() &&
-            self.has_valid_height() &&
-            self.has_valid_hair_color() &&
-            self.has_valid_eye_color() &&
-            self.has_valid_passport_id()
-    }
-
-    fn has_valid_birth_year(&self) -> bool {
-        match self.fields.get("byr") {
-            None => false,
-            Some(s) => {
-                match s.parse::<i32>() {
-                    Ok(n) if n >= 1920 && n <= 2002 => true,
-                    _ => false,
-                }
-            }
-        }
-    }
-
-    fn has_valid_issue_year(&self) -> bool {
-        match self.fields.get("iyr") {
-            None => false,
-            Some(s) => {
-                match s.parse::<i32>() {
-                    Ok(n) if n >= 2010 && n <= 2020 => true,
-                    _ => false,
-                }
-            }
-        }
-    }
-
-    fn has_valid_exp_year(&self) -> bool {
-        match self.fields.get("eyr") {
-            None => false,
-            Some(s) => {
-                match s.parse::<i32>() {
-                    Ok(n) if n >= 2020 && n <= 2030 => true,
-                    _ => false,
-                }
-            }
+    let mut validated = Vec::new();
+    let mut unconvertible = 0;
+    for passport in passports.iter().filter(|p| p.is_valid(schema)) {
+        match ValidatedPassport::from_passport(passport, schema) {
+            Ok(p) => validated.push(p),
+            Err(_) => unconvertible += 1,
         }
     }
 
-    fn has_valid_height(&self) -> bool {
-        match self.fields.get("hgt") {
-            Some(s) if s.ends_with("cm") => {
-                match s[..s.len() - 2].parse::<i32>() {
-                    Ok(n) if n >= 150 && n <= 193 => true,
-                    _ => false,
-                }
-            }
-            Some(s) if s.ends_with("in") => {
-                match s[..s.len() - 2].parse::<i32>() {
-                    Ok(n) if n >= 59 && n <= 76 => true,
-                    _ => false,
-                }
-            }
-            _ => false,
-        }
+    let file = fs::File::create(export_path)
+        .expect("Could not create the export file");
+    export(&validated, format, io::BufWriter::new(file))
+        .expect("Something went wrong writing the export file");
+    println!("Exported {} valid passports to {}", validated.len(), export_path);
+    if unconvertible > 0 {
+        println!("Skipped {} valid passports whose fields couldn't be read as years, heights and colors", unconvertible);
     }
+}
 
-    fn has_valid_hair_color(&self) -> bool {
-        match self.fields.get("hcl") {
-            Some(s) if s.starts_with('#') => {
-                s.chars().skip(1).all(|c| {
-                    (c >= '0' && c <= '9') || (c >= 'a' && c <= 'f')
-                })
-            }
-            _ => false,
+fn report(passports: &[Passport], schema: &Schema) {
+    for (i, passport) in passports.iter().enumerate() {
+        let violations = passport.validate(schema);
+        if violations.is_empty() {
+            continue;
         }
-    }
 
-    fn has_valid_eye_color(&self) -> bool {
-        match self.fields.get("ecl") {
-            Some(s) => *s == "amb" || *s == "blu" || *s == "brn" || *s == "gry" || *s == "grn" || *s == "hzl" || *s == "oth",
-            _ => false,
+        if violations.iter().any(|violation| violation.invalidates()) {
+            println!("Passport {} is invalid:", i + 1);
+        } else {
+            println!("Passport {} is valid, but:", i + 1);
         }
-    }
-
-    fn has_valid_passport_id(&self) -> bool {
-        match self.fields.get("pid") {
-            Some(s) => s.len() == 9 && s.chars().all(char::is_numeric),
-            _ => false,
+        for violation in violations {
+            println!("  {}", violation);
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use crate::schema::{Schema, RuleError};
+
+#[derive(Debug, Clone)]
+pub struct ParseError;
+
+pub struct Passport<'a> {
+    fields: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> TryFrom<&'a str> for Passport<'a> {
+    type Error = ParseError;
+
+    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
+        let fields = s.split([' ', '\n']).map(|field_str| {
+            let v = field_str.split(':').collect::<Vec<&str>>();
+            if v.len() != 2 {
+                Err(ParseError)
+            } else {
+                Ok((v[0], v[1]))
+            }
+        }).collect::<Result<HashMap<&str, &str>, ParseError>>()?;
+
+        Ok(Passport { fields })
+    }
+}
+
+impl<'a> Passport<'a> {
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.fields.get(name).cloned()
+    }
+
+    pub fn contains_fields(&self, fields: &[&str]) -> bool {
+        fields.iter().all(|field| self.fields.contains_key(field))
+    }
+
+    pub fn is_valid(&self, schema: &Schema) -> bool {
+        self.validate(schema).iter().all(|violation| !violation.invalidates())
+    }
+
+    pub fn validate(&self, schema: &Schema) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        for (name, spec) in schema.fields() {
+            match self.fields.get(name) {
+                Some(value) => {
+                    if let Err(err) = spec.rule.check(value) {
+                        violations.push(Violation::new(name, value, err));
+                    }
+                }
+                None if spec.required => {
+                    violations.push(Violation::MissingField(name.to_owned()));
+                }
+                None => {}
+            }
+        }
+
+        let mut unknown_fields = self.fields.keys()
+            .filter(|name| !schema.contains_field(name))
+            .collect::<Vec<_>>();
+        unknown_fields.sort();
+        for name in unknown_fields {
+            violations.push(Violation::UnknownField(name.to_string()));
+        }
+
+        violations
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Violation {
+    MissingField(String),
+    OutOfRange { field: String, value: String },
+    BadFormat { field: String, value: String },
+    UnknownField(String),
+}
+
+impl Violation {
+    fn new(field: &str, value: &str, err: RuleError) -> Self {
+        let field = field.to_owned();
+        let value = value.to_owned();
+        match err {
+            RuleError::OutOfRange => Violation::OutOfRange { field, value },
+            RuleError::BadFormat => Violation::BadFormat { field, value },
+        }
+    }
+
+    // fields the schema doesn't know about are worth reporting, but the schema has no say over
+    // them so they don't make the passport invalid
+    pub fn invalidates(&self) -> bool {
+        !matches!(self, Violation::UnknownField(_))
+    }
+
+    pub fn field(&self) -> &str {
+        match self {
+            Violation::MissingField(field) => field,
+            Violation::OutOfRange { field, .. } => field,
+            Violation::BadFormat { field, .. } => field,
+            Violation::UnknownField(field) => field,
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::MissingField(field) => write!(f, "missing required field {}", field),
+            Violation::OutOfRange { field, value } => write!(f, "{} is out of range: {}", field, value),
+            Violation::BadFormat { field, value } => write!(f, "{} is badly formatted: {}", field, value),
+            Violation::UnknownField(field) => write!(f, "unknown field {}", field),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use crate::passport::{Passport, Violation};
+    use crate::schema::{FieldRule, Schema};
+
+    fn schema() -> Schema {
+        Schema::new()
+            .field("byr", true, FieldRule::Year { min: 1920, max: 2002 })
+            .field("hcl", true, FieldRule::HexColor { digits: Some(6) })
+            .field("pid", false, FieldRule::Digits { length: 9 })
+    }
+
+    fn validate(s: &str) -> Vec<Violation> {
+        Passport::try_from(s).unwrap().validate(&schema())
+    }
+
+    #[test]
+    fn reports_each_kind_of_violation() {
+        assert_eq!(validate("byr:1990 hcl:#123abc"), vec![]);
+        assert_eq!(validate("hcl:#123abc"), vec![Violation::MissingField("byr".to_owned())]);
+        assert_eq!(validate("byr:2003 hcl:#123abc"), vec![
+            Violation::OutOfRange { field: "byr".to_owned(), value: "2003".to_owned() },
+        ]);
+        assert_eq!(validate("byr:1990 hcl:123abc pid:12"), vec![
+            Violation::BadFormat { field: "hcl".to_owned(), value: "123abc".to_owned() },
+            Violation::BadFormat { field: "pid".to_owned(), value: "12".to_owned() },
+        ]);
+        assert_eq!(validate("byr:1990 hcl:#123abc cid:1 xyz:2"), vec![
+            Violation::UnknownField("cid".to_owned()),
+            Violation::UnknownField("xyz".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn unknown_fields_do_not_invalidate() {
+        let passport = Passport::try_from("byr:1990 hcl:#123abc cid:1").unwrap();
+        assert!(passport.is_valid(&schema()));
+
+        let passport = Passport::try_from("byr:1990 cid:1").unwrap();
+        assert!(!passport.is_valid(&schema()));
+    }
+}
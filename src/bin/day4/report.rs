@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use crate::passport::{Passport, Violation};
+use crate::schema::Schema;
+
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub total: usize,
+    pub valid: usize,
+    pub failures: BTreeMap<String, FieldFailures>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FieldFailures {
+    pub missing: usize,
+    pub out_of_range: usize,
+    pub bad_format: usize,
+    pub unknown: usize,
+}
+
+impl FieldFailures {
+    pub fn total(&self) -> usize {
+        self.missing + self.out_of_range + self.bad_format + self.unknown
+    }
+
+    fn record(&mut self, violation: &Violation) {
+        match violation {
+            Violation::MissingField(_) => self.missing += 1,
+            Violation::OutOfRange { .. } => self.out_of_range += 1,
+            Violation::BadFormat { .. } => self.bad_format += 1,
+            Violation::UnknownField(_) => self.unknown += 1,
+        }
+    }
+}
+
+impl Summary {
+    pub fn new(passports: &[Passport], schema: &Schema) -> Self {
+        let mut summary = Summary::default();
+
+        for passport in passports {
+            let violations = passport.validate(schema);
+            summary.total += 1;
+            if violations.iter().all(|violation| !violation.invalidates()) {
+                summary.valid += 1;
+            }
+
+            for violation in &violations {
+                summary.failures.entry(violation.field().to_owned())
+                    .or_default()
+                    .record(violation);
+            }
+        }
+
+        summary
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} of {} passports are valid", self.valid, self.total)?;
+        writeln!(f, "{:<6} {:>7} {:>7} {:>7} {:>7} {:>7}", "field", "total", "missing", "range", "format", "unknown")?;
+        for (field, failures) in &self.failures {
+            writeln!(f, "{:<6} {:>7} {:>7} {:>7} {:>7} {:>7}",
+                     field,
+                     failures.total(),
+                     failures.missing,
+                     failures.out_of_range,
+                     failures.bad_format,
+                     failures.unknown)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use crate::passport::Passport;
+    use crate::report::Summary;
+    use crate::schema::{FieldRule, Schema};
+
+    #[test]
+    fn summarizes_failures_by_field() {
+        let schema = Schema::new()
+            .field("byr", true, FieldRule::Year { min: 1920, max: 2002 })
+            .field("pid", true, FieldRule::Digits { length: 9 });
+        let passports = ["byr:1990 pid:000000001", "byr:2003 pid:1", "pid:000000001 cid:5", "byr:abc"]
+            .iter()
+            .map(|s| Passport::try_from(*s).unwrap())
+            .collect::<Vec<Passport>>();
+
+        let summary = Summary::new(&passports, &schema);
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.valid, 1);
+
+        let byr = summary.failures["byr"];
+        assert_eq!((byr.missing, byr.out_of_range, byr.bad_format, byr.unknown), (1, 1, 1, 0));
+        let pid = summary.failures["pid"];
+        assert_eq!((pid.missing, pid.out_of_range, pid.bad_format, pid.unknown), (1, 0, 1, 0));
+        assert_eq!(summary.failures["cid"].unknown, 1);
+
+        assert_eq!(summary.to_string(), "\
+1 of 4 passports are valid
+field    total missing   range  format unknown
+byr          3       1       1       1       0
+cid          1       0       0       0       1
+pid          2       1       0       1       0
+");
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Schema {
+    fields: BTreeMap<String, FieldSpec>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldSpec {
+    #[serde(default = "required_by_default")]
+    pub required: bool,
+    #[serde(flatten)]
+    pub rule: FieldRule,
+}
+
+fn required_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FieldRule {
+    Any,
+    Year { min: u16, max: u16 },
+    Measurement { units: BTreeMap<String, Range> },
+    HexColor {
+        #[serde(default)]
+        digits: Option<usize>,
+    },
+    OneOf { values: Vec<String> },
+    Digits { length: usize },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Range {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl Range {
+    pub fn contains(&self, n: u32) -> bool {
+        n >= self.min && n <= self.max
+    }
+}
+
+impl Schema {
+    pub fn from_toml(s: &str) -> Result<Schema, toml::de::Error> {
+        toml::from_str(s)
+    }
+
+    pub fn from_json(s: &str) -> Result<Schema, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+
+    pub fn new() -> Self {
+        Schema { fields: BTreeMap::new() }
+    }
+
+    pub fn field(mut self, name: &str, required: bool, rule: FieldRule) -> Self {
+        self.fields.insert(name.to_owned(), FieldSpec { required, rule });
+        self
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &FieldSpec)> {
+        self.fields.iter().map(|(name, spec)| (&name[..], spec))
+    }
+
+    pub fn contains_field(&self, name: &str) -> bool {
+        self.fields.contains_key(name)
+    }
+
+    pub fn required_fields(&self) -> Vec<&str> {
+        self.fields()
+            .filter(|(_, spec)| spec.required)
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+impl Default for Schema {
+    fn default() -> Self {
+        let mut units = BTreeMap::new();
+        units.insert("cm".to_owned(), Range { min: 150, max: 193 });
+        units.insert("in".to_owned(), Range { min: 59, max: 76 });
+
+        let eye_colors = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+
+        Schema::new()
+            .field("byr", true, FieldRule::Year { min: 1920, max: 2002 })
+            .field("iyr", true, FieldRule::Year { min: 2010, max: 2020 })
+            .field("eyr", true, FieldRule::Year { min: 2020, max: 2030 })
+            .field("hgt", true, FieldRule::Measurement { units })
+            .field("hcl", true, FieldRule::HexColor { digits: None })
+            .field("ecl", true, FieldRule::OneOf { values: eye_colors.iter().map(|s| s.to_string()).collect() })
+            .field("pid", true, FieldRule::Digits { length: 9 })
+            .field("cid", false, FieldRule::Any)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RuleError {
+    OutOfRange,
+    BadFormat,
+}
+
+impl FieldRule {
+    pub fn check(&self, value: &str) -> Result<(), RuleError> {
+        match self {
+            FieldRule::Any => Ok(()),
+            FieldRule::Year { min, max } => {
+                let n = value.parse::<u16>().map_err(|_| RuleError::BadFormat)?;
+                if n >= *min && n <= *max { Ok(()) } else { Err(RuleError::OutOfRange) }
+            }
+            FieldRule::Measurement { units } => {
+                let (unit, range) = units.iter()
+                    .find(|(unit, _)| value.ends_with(&unit[..]))
+                    .ok_or(RuleError::BadFormat)?;
+                let n = value[..value.len() - unit.len()].parse::<u32>()
+                    .map_err(|_| RuleError::BadFormat)?;
+                if range.contains(n) { Ok(()) } else { Err(RuleError::OutOfRange) }
+            }
+            FieldRule::HexColor { digits } => {
+                match value.strip_prefix('#') {
+                    Some(hex) if digits.is_none_or(|n| hex.len() == n) && hex.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')) => Ok(()),
+                    _ => Err(RuleError::BadFormat),
+                }
+            }
+            FieldRule::OneOf { values } => {
+                if values.iter().any(|v| v == value) { Ok(()) } else { Err(RuleError::OutOfRange) }
+            }
+            FieldRule::Digits { length } => {
+                if value.len() == *length && value.chars().all(char::is_numeric) {
+                    Ok(())
+                } else {
+                    Err(RuleError::BadFormat)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use crate::passport::Passport;
+    use crate::schema::{FieldRule, RuleError, Schema};
+
+    // the rules as they were written by hand before the schema existed
+    fn hand_written_is_valid(p: &Passport) -> bool {
+        let year = |name, min, max| matches!(p.get(name).map(|s| s.parse::<i32>()), Some(Ok(n)) if n >= min && n <= max);
+        let height = match p.get("hgt") {
+            Some(s) if s.ends_with("cm") => matches!(s[..s.len() - 2].parse::<i32>(), Ok(n) if (150..=193).contains(&n)),
+            Some(s) if s.ends_with("in") => matches!(s[..s.len() - 2].parse::<i32>(), Ok(n) if (59..=76).contains(&n)),
+            _ => false,
+        };
+        let hair = match p.get("hcl") {
+            Some(s) if s.starts_with('#') => s.chars().skip(1).all(|c| matches!(c, '0'..='9' | 'a'..='f')),
+            _ => false,
+        };
+        let eyes = matches!(p.get("ecl"), Some("amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth"));
+        let pid = matches!(p.get("pid"), Some(s) if s.len() == 9 && s.chars().all(char::is_numeric));
+
+        year("byr", 1920, 2002) && year("iyr", 2010, 2020) && year("eyr", 2020, 2030) && height && hair && eyes && pid
+    }
+
+    #[test]
+    fn default_schema_matches_hand_written_rules() {
+        let base = "byr:1980 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704";
+        let variations = [
+            "byr:1919", "byr:1920", "byr:2002", "byr:2003", "byr:02000", "byr:+1990", "byr:abcd",
+            "iyr:2009", "iyr:2010", "iyr:2020", "iyr:2021",
+            "eyr:2019", "eyr:2020", "eyr:2030", "eyr:2031",
+            "hgt:149cm", "hgt:150cm", "hgt:193cm", "hgt:194cm", "hgt:58in", "hgt:59in", "hgt:76in",
+            "hgt:77in", "hgt:190", "hgt:cm", "hgt:60ft",
+            "hcl:#123abc", "hcl:#123abz", "hcl:123abc", "hcl:#abc", "hcl:#", "hcl:#123ABC",
+            "ecl:amb", "ecl:oth", "ecl:wat", "ecl:",
+            "pid:000000001", "pid:0123456789", "pid:12345678a", "pid:12345678",
+            "cid:100",
+        ];
+
+        let schema = Schema::default();
+        for variation in variations.iter() {
+            let name = &variation[..3];
+            let fields = base.split(' ')
+                .filter(|field| !field.starts_with(name))
+                .chain(std::iter::once(*variation))
+                .collect::<Vec<&str>>()
+                .join(" ");
+            let passport = Passport::try_from(&fields[..]).unwrap();
+            assert_eq!(passport.is_valid(&schema), hand_written_is_valid(&passport), "{}", variation);
+        }
+    }
+
+    #[test]
+    fn default_rule_boundaries() {
+        let schema = Schema::default();
+        let check = |name: &str, value: &str| {
+            schema.fields().find(|(n, _)| *n == name).unwrap().1.rule.check(value)
+        };
+
+        assert_eq!(check("byr", "2002"), Ok(()));
+        assert_eq!(check("byr", "2003"), Err(RuleError::OutOfRange));
+        assert_eq!(check("iyr", "2010"), Ok(()));
+        assert_eq!(check("iyr", "2009"), Err(RuleError::OutOfRange));
+        assert_eq!(check("eyr", "2030"), Ok(()));
+        assert_eq!(check("eyr", "2031"), Err(RuleError::OutOfRange));
+        assert_eq!(check("hgt", "60in"), Ok(()));
+        assert_eq!(check("hgt", "190cm"), Ok(()));
+        assert_eq!(check("hgt", "190in"), Err(RuleError::OutOfRange));
+        assert_eq!(check("hgt", "190"), Err(RuleError::BadFormat));
+        assert_eq!(check("hcl", "#123abc"), Ok(()));
+        assert_eq!(check("hcl", "#123abz"), Err(RuleError::BadFormat));
+        assert_eq!(check("hcl", "123abc"), Err(RuleError::BadFormat));
+        assert_eq!(check("ecl", "brn"), Ok(()));
+        assert_eq!(check("ecl", "wat"), Err(RuleError::OutOfRange));
+        assert_eq!(check("pid", "000000001"), Ok(()));
+        assert_eq!(check("pid", "0123456789"), Err(RuleError::BadFormat));
+        assert_eq!(check("cid", "anything"), Ok(()));
+        assert_eq!(schema.required_fields(), vec!["byr", "ecl", "eyr", "hcl", "hgt", "iyr", "pid"]);
+    }
+
+    fn assert_loaded_schema(schema: &Schema) {
+        assert_eq!(schema.required_fields(), vec!["byr", "hcl"]);
+        assert!(schema.contains_field("cid"));
+
+        let rule = |name: &str| &schema.fields().find(|(n, _)| *n == name).unwrap().1.rule;
+        assert!(matches!(rule("byr"), FieldRule::Year { min: 1900, max: 2000 }));
+        assert!(matches!(rule("hcl"), FieldRule::HexColor { digits: Some(3) }));
+        assert!(matches!(rule("cid"), FieldRule::Any));
+        assert_eq!(rule("hgt").check("2m"), Ok(()));
+        assert_eq!(rule("hgt").check("3m"), Err(RuleError::OutOfRange));
+        assert_eq!(rule("hcl").check("#abc"), Ok(()));
+        assert_eq!(rule("hcl").check("#abcdef"), Err(RuleError::BadFormat));
+    }
+
+    #[test]
+    fn loads_toml_schema() {
+        let schema = Schema::from_toml(r#"
+            [fields.byr]
+            type = "year"
+            min = 1900
+            max = 2000
+
+            [fields.hgt]
+            type = "measurement"
+            required = false
+            units = { m = { min = 1, max = 2 } }
+
+            [fields.hcl]
+            type = "hex_color"
+            digits = 3
+
+            [fields.cid]
+            type = "any"
+            required = false
+        "#).unwrap();
+        assert_loaded_schema(&schema);
+    }
+
+    #[test]
+    fn loads_json_schema() {
+        let schema = Schema::from_json(r#"{
+            "fields": {
+                "byr": { "type": "year", "min": 1900, "max": 2000 },
+                "hgt": { "type": "measurement", "required": false, "units": { "m": { "min": 1, "max": 2 } } },
+                "hcl": { "type": "hex_color", "digits": 3 },
+                "cid": { "type": "any", "required": false }
+            }
+        }"#).unwrap();
+        assert_loaded_schema(&schema);
+
+        assert!(Schema::from_json(r#"{ "fields": { "byr": { "type": "decade" } } }"#).is_err());
+    }
+}
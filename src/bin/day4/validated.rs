@@ -0,0 +1,232 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use serde::Serialize;
+use crate::passport::{Passport, ParseError, Violation};
+use crate::schema::Schema;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidatedPassport {
+    pub birth_year: u16,
+    pub issue_year: u16,
+    pub expiration_year: u16,
+    pub height: Height,
+    pub hair_color: Rgb,
+    pub eye_color: EyeColor,
+    pub passport_id: String,
+    pub country_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+#[serde(tag = "unit", content = "value", rename_all = "snake_case")]
+pub enum Height {
+    Cm(u32),
+    In(u32),
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EyeColor {
+    Amber,
+    Blue,
+    Brown,
+    Gray,
+    Green,
+    Hazel,
+    Other,
+}
+
+impl ValidatedPassport {
+    // the schema decides whether the passport is valid at all, but its fields still have to make
+    // sense as years, heights and so on for the passport to be converted
+    pub fn from_passport(passport: &Passport, schema: &Schema) -> Result<Self, Vec<Violation>> {
+        let violations = passport.validate(schema).into_iter()
+            .filter(Violation::invalidates)
+            .collect::<Vec<Violation>>();
+        if !violations.is_empty() {
+            return Err(violations);
+        }
+
+        Ok(ValidatedPassport {
+            birth_year: field(passport, "byr")?,
+            issue_year: field(passport, "iyr")?,
+            expiration_year: field(passport, "eyr")?,
+            height: field(passport, "hgt")?,
+            hair_color: field(passport, "hcl")?,
+            eye_color: field(passport, "ecl")?,
+            passport_id: field(passport, "pid")?,
+            country_id: passport.get("cid").map(str::to_owned),
+        })
+    }
+}
+
+impl TryFrom<Passport<'_>> for ValidatedPassport {
+    type Error = Vec<Violation>;
+
+    fn try_from(passport: Passport<'_>) -> Result<Self, Self::Error> {
+        ValidatedPassport::from_passport(&passport, &Schema::default())
+    }
+}
+
+fn field<T: FromStr>(passport: &Passport, name: &str) -> Result<T, Vec<Violation>> {
+    let value = passport.get(name)
+        .ok_or_else(|| vec![Violation::MissingField(name.to_owned())])?;
+    value.parse::<T>().map_err(|_| {
+        vec![Violation::BadFormat { field: name.to_owned(), value: value.to_owned() }]
+    })
+}
+
+impl FromStr for Height {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(n) = s.strip_suffix("cm") {
+            n.parse().map(Height::Cm).map_err(|_| ParseError)
+        } else if let Some(n) = s.strip_suffix("in") {
+            n.parse().map(Height::In).map_err(|_| ParseError)
+        } else {
+            Err(ParseError)
+        }
+    }
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Height::Cm(n) => write!(f, "{}cm", n),
+            Height::In(n) => write!(f, "{}in", n),
+        }
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').ok_or(ParseError)?;
+        if hex.len() != 6 || !hex.is_ascii() {
+            return Err(ParseError);
+        }
+
+        let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| ParseError);
+        Ok(Rgb { r: component(0)?, g: component(2)?, b: component(4)? })
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl FromStr for EyeColor {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "amb" => Ok(EyeColor::Amber),
+            "blu" => Ok(EyeColor::Blue),
+            "brn" => Ok(EyeColor::Brown),
+            "gry" => Ok(EyeColor::Gray),
+            "grn" => Ok(EyeColor::Green),
+            "hzl" => Ok(EyeColor::Hazel),
+            "oth" => Ok(EyeColor::Other),
+            _ => Err(ParseError),
+        }
+    }
+}
+
+impl fmt::Display for EyeColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            EyeColor::Amber => "amb",
+            EyeColor::Blue => "blu",
+            EyeColor::Brown => "brn",
+            EyeColor::Gray => "gry",
+            EyeColor::Green => "grn",
+            EyeColor::Hazel => "hzl",
+            EyeColor::Other => "oth",
+        };
+        write!(f, "{}", code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+    use crate::passport::{Passport, Violation};
+    use crate::schema::{FieldRule, Schema};
+    use crate::validated::{EyeColor, Height, Rgb, ValidatedPassport};
+
+    #[test]
+    fn parses_heights() {
+        assert_eq!("183cm".parse::<Height>().ok(), Some(Height::Cm(183)));
+        assert_eq!("60in".parse::<Height>().ok(), Some(Height::In(60)));
+        assert!("60".parse::<Height>().is_err());
+        assert!("cm".parse::<Height>().is_err());
+        assert!("-5in".parse::<Height>().is_err());
+        assert_eq!(Height::Cm(183).to_string(), "183cm");
+    }
+
+    #[test]
+    fn parses_colors() {
+        assert_eq!("#0a10ff".parse::<Rgb>().ok(), Some(Rgb { r: 10, g: 16, b: 255 }));
+        assert!("0a10ff".parse::<Rgb>().is_err());
+        assert!("#0a10f".parse::<Rgb>().is_err());
+        assert!("#0a10fg".parse::<Rgb>().is_err());
+        assert!("#0a10fé".parse::<Rgb>().is_err());
+        assert_eq!(Rgb { r: 10, g: 16, b: 255 }.to_string(), "#0a10ff");
+
+        let codes = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+        for code in codes.iter() {
+            assert_eq!(code.parse::<EyeColor>().unwrap().to_string(), *code);
+        }
+        assert_eq!("hzl".parse::<EyeColor>().ok(), Some(EyeColor::Hazel));
+        assert!("wat".parse::<EyeColor>().is_err());
+    }
+
+    #[test]
+    fn validates_against_the_given_schema() {
+        let passport = Passport::try_from("byr:1900 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704").unwrap();
+        assert_eq!(ValidatedPassport::from_passport(&passport, &Schema::default()).err(), Some(vec![
+            Violation::OutOfRange { field: "byr".to_owned(), value: "1900".to_owned() },
+        ]));
+
+        let lenient = Schema::new().field("byr", true, FieldRule::Any);
+        let validated = ValidatedPassport::from_passport(&passport, &lenient).unwrap();
+        assert_eq!(validated.birth_year, 1900);
+        assert_eq!(validated.height, Height::In(74));
+
+        let passport = Passport::try_from("byr:1980 iyr:2012 eyr:2030 hgt:74 hcl:#623a2f ecl:grn pid:087499704").unwrap();
+        assert_eq!(ValidatedPassport::from_passport(&passport, &lenient).err(), Some(vec![
+            Violation::BadFormat { field: "hgt".to_owned(), value: "74".to_owned() },
+        ]));
+    }
+
+    #[test]
+    fn converts_with_the_default_schema() {
+        let passport = Passport::try_from("pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f cid:88").unwrap();
+        let validated = ValidatedPassport::try_from(passport).unwrap();
+        assert_eq!(validated.birth_year, 1980);
+        assert_eq!(validated.height, Height::In(74));
+        assert_eq!(validated.hair_color, Rgb { r: 0x62, g: 0x3a, b: 0x2f });
+        assert_eq!(validated.eye_color, EyeColor::Green);
+        assert_eq!(validated.passport_id, "087499704");
+        assert_eq!(validated.country_id, Some("88".to_owned()));
+
+        let passport = Passport::try_from("byr:1900 iyr:2012 eyr:2030 hgt:74in hcl:#623a2f ecl:grn pid:087499704").unwrap();
+        assert_eq!(ValidatedPassport::try_from(passport).err(), Some(vec![
+            Violation::OutOfRange { field: "byr".to_owned(), value: "1900".to_owned() },
+        ]));
+
+        let passport = Passport::try_from("byr:1980 iyr:2012 eyr:2030 hcl:#623a2f ecl:grn pid:087499704").unwrap();
+        assert_eq!(ValidatedPassport::try_from(passport).err(), Some(vec![Violation::MissingField("hgt".to_owned())]));
+    }
+}
@@ -1,11 +1,15 @@
+use std::fmt;
 use std::str::FromStr;
 
+// the original partitioning decoder, kept around to check the bit-based one against
+#[cfg(test)]
 #[derive(Debug, Eq, PartialEq)]
 pub struct BinarySpace {
     min: i32,
     max: i32,
 }
 
+#[cfg(test)]
 impl BinarySpace {
     fn width(&self) -> i32 {
         self.max - self.min
@@ -24,47 +28,117 @@ impl BinarySpace {
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PlaneGeometry {
+    row_bits: u32,
+    col_bits: u32,
+}
+
+impl Default for PlaneGeometry {
+    fn default() -> Self {
+        PlaneGeometry { row_bits: 7, col_bits: 3 }
+    }
+}
+
+// seat ids are i32s, and leaving the sign bit alone means row and column counts and the ids
+// either side of a seat never overflow
+pub const MAX_PASS_BITS: u32 = 30;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct GeometryError {
+    pub row_bits: u32,
+    pub col_bits: u32,
+}
+
+impl fmt::Display for GeometryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} row bits and {} column bits make for more than {} bits of seat id",
+               self.row_bits, self.col_bits, MAX_PASS_BITS)
+    }
+}
+
+impl PlaneGeometry {
+    pub fn new(row_bits: u32, col_bits: u32) -> Result<Self, GeometryError> {
+        match row_bits.checked_add(col_bits) {
+            Some(bits) if bits <= MAX_PASS_BITS => Ok(PlaneGeometry { row_bits, col_bits }),
+            _ => Err(GeometryError { row_bits, col_bits }),
+        }
+    }
+
+    pub fn rows(&self) -> i32 {
+        1 << self.row_bits
+    }
+
+    pub fn cols(&self) -> i32 {
+        1 << self.col_bits
+    }
+
+    pub fn pass_len(&self) -> usize {
+        (self.row_bits + self.col_bits) as usize
+    }
+
+    pub fn decode(&self, s: &str) -> Result<Seat, ParseError> {
+        let found = s.chars().count();
+        if found != self.pass_len() {
+            return Err(ParseError::BadLength { expected: self.pass_len(), found });
+        }
+
+        let mut bits = 0;
+        for (position, c) in s.chars().enumerate() {
+            let is_row = position < self.row_bits as usize;
+            let bit = match (is_row, c) {
+                (true, 'F') | (false, 'L') => 0,
+                (true, 'B') | (false, 'R') => 1,
+                _ => return Err(ParseError::BadCharacter { position, found: c }),
+            };
+            bits = (bits << 1) | bit;
+        }
+
+        Ok(Seat::from_id(bits, self))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct Seat {
     row: i32,
     col: i32,
 }
 
 impl Seat {
-    pub fn id(&self) -> i32 {
-        self.row * 8 + self.col
+    pub fn from_id(id: i32, geometry: &PlaneGeometry) -> Self {
+        Seat { row: id >> geometry.col_bits, col: id & (geometry.cols() - 1) }
+    }
+
+    pub fn id(&self, geometry: &PlaneGeometry) -> i32 {
+        (self.row << geometry.col_bits) | self.col
+    }
+
+    pub fn encode(&self, geometry: &PlaneGeometry) -> String {
+        let row_chars = (0..geometry.row_bits).rev()
+            .map(|bit| if self.row & (1 << bit) == 0 { 'F' } else { 'B' });
+        let col_chars = (0..geometry.col_bits).rev()
+            .map(|bit| if self.col & (1 << bit) == 0 { 'L' } else { 'R' });
+        row_chars.chain(col_chars).collect()
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct ParseError;
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseError {
+    BadLength { expected: usize, found: usize },
+    BadCharacter { position: usize, found: char },
+}
 
 impl FromStr for Seat {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (row_str, col_str) = s.split_at(7);
-        let row_num = row_str.chars().fold(BinarySpace { min: 0, max: 128 }, |space, c| {
-            match c {
-                'F' => space.partition_lower(),
-                'B' => space.partition_higher(),
-                _ => panic!(),
-            }
-        }).min;
-        let col_num = col_str.chars().fold(BinarySpace { min: 0, max: 8 }, |space, c| {
-            match c {
-                'L' => space.partition_lower(),
-                'R' => space.partition_higher(),
-                _ => panic!(),
-            }
-        }).min;
-        Ok(Seat { row: row_num, col: col_num })
+        PlaneGeometry::default().decode(s)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day5::{BinarySpace, ParseError, Seat};
+    use crate::day5::{BinarySpace, GeometryError, ParseError, PlaneGeometry, Seat};
 
     #[test]
     fn binary_space_width() {
@@ -101,4 +175,65 @@ mod tests {
         assert_eq!("FBFBBFFRLR".parse::<Seat>()?, Seat { row: 44, col: 5 });
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn seat_encode() -> Result<(), ParseError> {
+        let geometry = PlaneGeometry::default();
+        assert_eq!(Seat { row: 44, col: 5 }.encode(&geometry), "FBFBBFFRLR");
+        for pass in &["BFFFBBFRRR", "FFFBBBFRRR", "BBFFBBFRLL"] {
+            assert_eq!(pass.parse::<Seat>()?.encode(&geometry), *pass);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn seat_decode_matches_binary_space() {
+        let geometry = PlaneGeometry::default();
+        for row in 0..geometry.rows() {
+            for col in 0..geometry.cols() {
+                let pass = Seat { row, col }.encode(&geometry);
+                let (row_str, col_str) = pass.split_at(7);
+                let row_num = row_str.chars().fold(BinarySpace { min: 0, max: 128 }, |space, c| {
+                    if c == 'F' { space.partition_lower() } else { space.partition_higher() }
+                }).min;
+                let col_num = col_str.chars().fold(BinarySpace { min: 0, max: 8 }, |space, c| {
+                    if c == 'L' { space.partition_lower() } else { space.partition_higher() }
+                }).min;
+                assert_eq!(geometry.decode(&pass), Ok(Seat { row: row_num, col: col_num }));
+            }
+        }
+    }
+
+    #[test]
+    fn seat_decode_custom_geometry() -> Result<(), ParseError> {
+        let geometry = PlaneGeometry::new(4, 2).unwrap();
+        let seat = geometry.decode("BFBBLR")?;
+        assert_eq!(seat, Seat { row: 11, col: 1 });
+        assert_eq!(seat.id(&geometry), 45);
+        assert_eq!(seat.encode(&geometry), "BFBBLR");
+        Ok(())
+    }
+
+    #[test]
+    fn seat_decode_errors() {
+        assert_eq!("FBFBBFFRL".parse::<Seat>(),
+                   Err(ParseError::BadLength { expected: 10, found: 9 }));
+        assert_eq!("FBFBBFRRLR".parse::<Seat>(),
+                   Err(ParseError::BadCharacter { position: 6, found: 'R' }));
+        assert_eq!("FBFBBFFRLX".parse::<Seat>(),
+                   Err(ParseError::BadCharacter { position: 9, found: 'X' }));
+    }
+
+    #[test]
+    fn geometry_limits() {
+        assert_eq!(PlaneGeometry::new(7, 40), Err(GeometryError { row_bits: 7, col_bits: 40 }));
+        assert_eq!(PlaneGeometry::new(u32::MAX, 1), Err(GeometryError { row_bits: u32::MAX, col_bits: 1 }));
+
+        assert_eq!(PlaneGeometry::new(20, 11), Err(GeometryError { row_bits: 20, col_bits: 11 }));
+
+        let geometry = PlaneGeometry::new(20, 10).unwrap();
+        let seat = geometry.decode(&format!("{}{}", "B".repeat(20), "R".repeat(10))).unwrap();
+        assert_eq!(seat.id(&geometry), (1 << 30) - 1);
+        assert_eq!(seat, Seat::from_id((1 << 30) - 1, &geometry));
+    }
+}
@@ -1,8 +1,10 @@
 mod day5;
+mod seat_map;
 
 use std::env;
 use std::fs;
-use crate::day5::{Seat, ParseError};
+use crate::day5::{Seat, ParseError, PlaneGeometry};
+use crate::seat_map::SeatMap;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -10,41 +12,60 @@ fn main() {
     let input_path = &args[1];
     println!("Reading input from {}", input_path);
 
+    let (flags, bits): (Vec<&String>, Vec<&String>) = args[2..].iter()
+        .partition(|arg| arg.starts_with("--"));
+    let geometry = match (bits.first(), bits.get(1)) {
+        (Some(row_bits), Some(col_bits)) => match (row_bits.parse(), col_bits.parse()) {
+            (Ok(row_bits), Ok(col_bits)) => match PlaneGeometry::new(row_bits, col_bits) {
+                Ok(geometry) => geometry,
+                Err(err) => {
+                    println!("Invalid plane geometry: {}", err);
+                    return;
+                }
+            },
+            _ => {
+                println!("The numbers of row and column bits must be non-negative integers");
+                return;
+            }
+        },
+        _ => PlaneGeometry::default(),
+    };
+
     let contents = fs::read_to_string(input_path)
         .expect("Something went wrong reading the file");
     let seats = contents.lines()
-        .map(|line| line.parse::<Seat>())
+        .map(|line| geometry.decode(line))
         .collect::<Result<Vec<Seat>, ParseError>>()
         .expect("Could not parse seats");
+    let seat_map = SeatMap::new(&seats, geometry);
+
+    part1(&seats, &geometry);
+    part2(&seat_map, &geometry);
 
-    part1(&seats);
-    part2(&seats);
+    for (seat, count) in seat_map.duplicates() {
+        println!("Seat {} ({}) appears on {} boarding passes", seat.id(&geometry), seat.encode(&geometry), count);
+    }
+    if flags.iter().any(|flag| flag.as_str() == "--chart") {
+        print!("{}", seat_map);
+    }
 }
 
-fn part1(seats: &Vec<Seat>) {
-    let highest_seat_id = seats.iter()
-        .map(|s| s.id())
-        .max()
+fn part1(seats: &[Seat], geometry: &PlaneGeometry) {
+    let highest_seat = seats.iter()
+        .max_by_key(|s| s.id(geometry))
         .unwrap();
 
-    println!("The highest seat ID is {}", highest_seat_id);
+    println!("The highest seat ID is {} ({})", highest_seat.id(geometry), highest_seat.encode(geometry));
 }
 
-fn part2(seats: &Vec<Seat>) {
-    let mut seat_ids = seats.iter()
-        .map(Seat::id)
-        .collect::<Vec<i32>>();
-    seat_ids.sort();
-
-    for i in 0..(seat_ids.len() - 1) {
-        let this_seat = seat_ids[i];
-        let next_seat =  seat_ids[i + 1];
-        if this_seat + 1 != next_seat {
-            println!("Your seat ID is {}", this_seat + 1);
-            return;
+fn part2(seat_map: &SeatMap, geometry: &PlaneGeometry) {
+    match seat_map.your_seat() {
+        Some(seat) => println!("Your seat ID is {}", seat.id(geometry)),
+        None => {
+            let empty_ids = seat_map.empty_seats().iter()
+                .map(|s| s.id(geometry).to_string())
+                .collect::<Vec<String>>();
+            println!("Didn't find a gap in the list of seats! Empty seats: [{}]", empty_ids.join(", "));
         }
     }
-
-    panic!("Didn't find a gap in the list of seats!")
-}
-
+}
\ No newline at end of file
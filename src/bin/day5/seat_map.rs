@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use crate::day5::{PlaneGeometry, Seat};
+
+pub struct SeatMap {
+    geometry: PlaneGeometry,
+    passes: BTreeMap<i32, u32>,
+    occupied: Option<(i32, i32)>,
+}
+
+impl SeatMap {
+    pub fn new(seats: &[Seat], geometry: PlaneGeometry) -> Self {
+        let mut passes = BTreeMap::new();
+        for seat in seats {
+            *passes.entry(seat.id(&geometry)).or_insert(0) += 1;
+        }
+
+        let first = passes.keys().next().cloned();
+        let last = passes.keys().next_back().cloned();
+        SeatMap { geometry, passes, occupied: first.zip(last) }
+    }
+
+    fn is_occupied(&self, id: i32) -> bool {
+        self.passes.contains_key(&id)
+    }
+
+    // seats at the very front and back of the plane don't exist, so anything before the first
+    // occupied seat or after the last one can't be a real empty seat
+    fn is_missing(&self, id: i32) -> bool {
+        match self.occupied {
+            Some((first, last)) => id < first || id > last,
+            None => true,
+        }
+    }
+
+    pub fn empty_seats(&self) -> Vec<Seat> {
+        self.passes.keys()
+            .zip(self.passes.keys().skip(1))
+            .flat_map(|(before, after)| before + 1..*after)
+            .map(|id| Seat::from_id(id, &self.geometry))
+            .collect()
+    }
+
+    pub fn your_seat(&self) -> Option<Seat> {
+        self.empty_seats().into_iter().find(|seat| {
+            let id = seat.id(&self.geometry);
+            self.is_occupied(id - 1) && self.is_occupied(id + 1)
+        })
+    }
+
+    pub fn duplicates(&self) -> Vec<(Seat, u32)> {
+        self.passes.iter()
+            .filter(|(_, n)| **n > 1)
+            .map(|(id, n)| (Seat::from_id(*id, &self.geometry), *n))
+            .collect()
+    }
+}
+
+impl fmt::Display for SeatMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..self.geometry.rows() {
+            write!(f, "{:>4} ", row)?;
+            for col in 0..self.geometry.cols() {
+                let id = (row * self.geometry.cols()) + col;
+                let c = match self.passes.get(&id) {
+                    None if self.is_missing(id) => ' ',
+                    None => '.',
+                    Some(1) => '#',
+                    Some(_) => 'D',
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::day5::{ParseError, PlaneGeometry, Seat};
+    use crate::seat_map::SeatMap;
+
+    #[test]
+    fn seat_map_analysis() -> Result<(), ParseError> {
+        let geometry = PlaneGeometry::new(2, 2).unwrap();
+        let seats = ["FBLR", "FBRL", "BFLL", "BFLR", "BFRR", "BFRR"].iter()
+            .map(|pass| geometry.decode(pass))
+            .collect::<Result<Vec<Seat>, ParseError>>()?;
+        let seat_map = SeatMap::new(&seats, geometry);
+
+        assert_eq!(seat_map.empty_seats(), vec![Seat::from_id(7, &geometry), Seat::from_id(10, &geometry)]);
+        assert_eq!(seat_map.your_seat(), Some(Seat::from_id(7, &geometry)));
+        assert_eq!(seat_map.duplicates(), vec![(Seat::from_id(11, &geometry), 2)]);
+        assert_eq!(seat_map.to_string(), "   0     \n   1  ##.\n   2 ##.D\n   3     \n");
+        Ok(())
+    }
+
+    #[test]
+    fn sparse_seat_map() {
+        let geometry = PlaneGeometry::new(20, 10).unwrap();
+        let ids = [5, 6, 8, 1 << 20, (1 << 20) + 2];
+        let seats = ids.iter().map(|id| Seat::from_id(*id, &geometry)).collect::<Vec<Seat>>();
+        let seat_map = SeatMap::new(&seats, geometry);
+
+        assert_eq!(seat_map.your_seat(), Some(Seat::from_id(7, &geometry)));
+        assert_eq!(seat_map.empty_seats().len(), (1 << 20) - 9 + 2);
+        assert_eq!(seat_map.duplicates(), vec![]);
+
+        let empty = SeatMap::new(&[], geometry);
+        assert_eq!(empty.empty_seats(), vec![]);
+        assert_eq!(empty.your_seat(), None);
+    }
+}
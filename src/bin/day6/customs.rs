@@ -0,0 +1,206 @@
+use std::collections::BTreeSet;
+use crate::query::Query;
+
+pub type Answers = u128;
+
+#[derive(Debug, Clone)]
+pub struct ParseError;
+
+#[derive(Debug, Clone)]
+pub struct Alphabet {
+    symbols: Vec<char>,
+}
+
+impl Alphabet {
+    pub fn new(symbols: &str) -> Result<Alphabet, ParseError> {
+        let symbols = symbols.chars().collect::<BTreeSet<char>>();
+        if symbols.len() > Answers::BITS as usize {
+            return Err(ParseError);
+        }
+
+        Ok(Alphabet { symbols: symbols.into_iter().collect() })
+    }
+
+    pub fn lowercase() -> Alphabet {
+        Alphabet { symbols: ('a'..='z').collect() }
+    }
+
+    pub fn from_input(s: &str) -> Result<Alphabet, ParseError> {
+        Alphabet::new(&s.chars().filter(|c| !c.is_whitespace()).collect::<String>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn symbol(&self, question: usize) -> char {
+        self.symbols[question]
+    }
+
+    fn index_of(&self, c: char) -> Option<usize> {
+        self.symbols.binary_search(&c).ok()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Group {
+    people: Vec<Answers>,
+}
+
+impl Group {
+    fn parse(s: &str, alphabet: &Alphabet) -> Result<Group, ParseError> {
+        let people = s.lines().filter(|line| !line.is_empty()).map(|line| {
+            line.chars().try_fold(0, |answers: Answers, c| {
+                let question = alphabet.index_of(c).ok_or(ParseError)?;
+                Ok(answers | (1 << question))
+            })
+        }).collect::<Result<Vec<Answers>, ParseError>>()?;
+
+        Ok(Group { people })
+    }
+
+    pub fn len(&self) -> usize {
+        self.people.len()
+    }
+
+    pub fn union(&self) -> Answers {
+        self.people.iter().fold(0, |acc, answers| acc | answers)
+    }
+
+    pub fn intersection(&self) -> Answers {
+        match self.people.split_first() {
+            Some((first, rest)) => rest.iter().fold(*first, |acc, answers| acc & answers),
+            None => 0,
+        }
+    }
+
+    pub fn answer_count(&self, question: usize) -> usize {
+        self.people.iter().filter(|answers| *answers & (1 << question) != 0).count()
+    }
+
+    // the fraction of questions answered by anyone in the group that everyone answered
+    pub fn agreement_ratio(&self) -> f64 {
+        let anyone = self.union().count_ones();
+        if anyone == 0 {
+            return 1.0;
+        }
+        self.intersection().count_ones() as f64 / anyone as f64
+    }
+
+    pub fn count_matching(&self, query: &Query, alphabet: &Alphabet) -> usize {
+        match query {
+            Query::Any => self.union().count_ones() as usize,
+            Query::All => self.intersection().count_ones() as usize,
+            Query::None => alphabet.len() - self.union().count_ones() as usize,
+            _ => (0..alphabet.len())
+                .filter(|question| query.matches(self.answer_count(*question), self.len()))
+                .count(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct QuestionStats {
+    pub question: char,
+    pub groups: usize,
+    pub people: usize,
+}
+
+pub struct Survey {
+    pub alphabet: Alphabet,
+    pub groups: Vec<Group>,
+}
+
+impl Survey {
+    pub fn parse(s: &str, alphabet: Alphabet) -> Result<Survey, ParseError> {
+        // extra blank lines between groups would otherwise turn into groups with nobody in them
+        let groups = s.split("\n\n")
+            .map(|group_str| Group::parse(group_str, &alphabet))
+            .filter(|group| !matches!(group, Ok(group) if group.people.is_empty()))
+            .collect::<Result<Vec<Group>, ParseError>>()?;
+
+        Ok(Survey { alphabet, groups })
+    }
+
+    pub fn evaluate(&self, query: &Query) -> usize {
+        self.groups.iter().map(|group| group.count_matching(query, &self.alphabet)).sum()
+    }
+
+    pub fn question_stats(&self) -> Vec<QuestionStats> {
+        (0..self.alphabet.len()).map(|question| {
+            let counts = self.groups.iter().map(|group| group.answer_count(question));
+            QuestionStats {
+                question: self.alphabet.symbol(question),
+                groups: counts.clone().filter(|n| *n > 0).count(),
+                people: counts.sum(),
+            }
+        }).collect()
+    }
+
+    pub fn agreement_ratios(&self) -> Vec<f64> {
+        self.groups.iter().map(Group::agreement_ratio).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::customs::{Alphabet, Group, QuestionStats, Survey};
+    use crate::query::Query;
+
+    const EXAMPLE: &str = "abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb";
+
+    fn survey(s: &str) -> Survey {
+        Survey::parse(s, Alphabet::lowercase()).unwrap()
+    }
+
+    #[test]
+    fn evaluates_example_queries() {
+        let survey = survey(EXAMPLE);
+        assert_eq!(survey.evaluate(&Query::Any), 11);
+        assert_eq!(survey.evaluate(&Query::All), 6);
+        assert_eq!(survey.evaluate(&Query::None), 5 * 26 - 11);
+        assert_eq!(survey.evaluate(&Query::Exactly(1)), 9);
+        assert_eq!(survey.evaluate(&Query::AtLeast(2)), 2);
+    }
+
+    #[test]
+    fn blank_lines_do_not_make_groups() {
+        let survey = survey("a\n\n\n\nb\n\n\nab");
+        assert_eq!(survey.groups.len(), 3);
+        assert_eq!(survey.evaluate(&Query::All), 4);
+        assert_eq!(survey.evaluate(&Query::Any), 4);
+
+        let nobody = Group::parse("", &Alphabet::lowercase()).unwrap();
+        assert_eq!(nobody.intersection(), 0);
+        assert_eq!(nobody.count_matching(&Query::All, &Alphabet::lowercase()), 0);
+    }
+
+    #[test]
+    fn builds_alphabets() {
+        let alphabet = Alphabet::new("cabba").unwrap();
+        assert_eq!(alphabet.len(), 3);
+        assert_eq!((0..3).map(|i| alphabet.symbol(i)).collect::<String>(), "abc");
+
+        let auto = Alphabet::from_input("x1\n\n#x\n y").unwrap();
+        assert_eq!((0..auto.len()).map(|i| auto.symbol(i)).collect::<String>(), "#1xy");
+        assert_eq!(Survey::parse("x1\n\n#x\ny", auto).unwrap().evaluate(&Query::Any), 5);
+
+        let ascii = (0..128u8).map(char::from).collect::<String>();
+        assert_eq!(Alphabet::new(&ascii).unwrap().len(), 128);
+        assert!(Alphabet::new(&format!("{}é", ascii)).is_err());
+
+        assert!(Survey::parse("ab\n\nA", Alphabet::lowercase()).is_err());
+    }
+
+    #[test]
+    fn question_and_group_stats() {
+        let survey = Survey::parse(EXAMPLE, Alphabet::new("abcd").unwrap()).unwrap();
+        assert_eq!(survey.question_stats(), vec![
+            QuestionStats { question: 'a', groups: 4, people: 8 },
+            QuestionStats { question: 'b', groups: 4, people: 4 },
+            QuestionStats { question: 'c', groups: 3, people: 3 },
+            QuestionStats { question: 'd', groups: 0, people: 0 },
+        ]);
+        assert_eq!(survey.agreement_ratios(), vec![1.0, 0.0, 1.0 / 3.0, 1.0, 1.0]);
+    }
+}
@@ -1,6 +1,10 @@
+mod customs;
+mod query;
+
 use std::env;
 use std::fs;
-use std::collections::HashSet;
+use crate::customs::{Alphabet, Survey};
+use crate::query::Query;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -10,35 +14,52 @@ fn main() {
 
     let contents = fs::read_to_string(input_path)
         .expect("Something went wrong reading the file");
-    let groups = parse_groups(&contents);
+    let contents = contents.trim_end();
+
+    let (flags, queries): (Vec<&String>, Vec<&String>) = args[2..].iter()
+        .partition(|arg| arg.starts_with("--"));
+    let alphabet = match flags.iter().find_map(|flag| flag.strip_prefix("--alphabet=")) {
+        Some("auto") => Alphabet::from_input(contents),
+        Some(symbols) => Alphabet::new(symbols),
+        None => Ok(Alphabet::lowercase()),
+    }.expect("The answer alphabet can have at most 128 symbols");
+    let survey = Survey::parse(contents, alphabet)
+        .expect("Could not parse groups of answers");
 
-    part1(&groups);
+    part1(&survey);
+    part2(&survey);
 
-    let groups2 = parse_groups2(&contents);
-    part2(&groups2);
+    for query_str in queries {
+        let query = Query::parse(query_str)
+            .expect("Could not parse query");
+        println!("The sum of counts of questions matching \"{}\" is {}", query_str, survey.evaluate(&query));
+    }
+
+    if flags.iter().any(|flag| flag.as_str() == "--stats") {
+        print_stats(&survey);
+    }
 }
 
-fn part1(groups: &Vec<HashSet<char>>) {
-    let result: usize = groups.iter().map(HashSet::len).sum();
+fn part1(survey: &Survey) {
+    let result = survey.evaluate(&Query::Any);
     println!("The sum of counts of questions answered by anyone is {}", result);
 }
 
-fn part2(groups: &Vec<HashSet<char>>) {
-    let result: usize = groups.iter().map(HashSet::len).sum();
+fn part2(survey: &Survey) {
+    let result = survey.evaluate(&Query::All);
     println!("The sum of counts of questions answered by everyone is {}", result);
 }
 
-fn parse_groups(contents: &str) -> Vec<HashSet<char>> {
-    contents.split("\n\n").map(|group_str| {
-        group_str.chars().filter(|c| *c >= 'a' && *c <= 'z').collect::<HashSet<char>>()
-    }).collect()
-}
+fn print_stats(survey: &Survey) {
+    println!("question  groups  people");
+    for stats in survey.question_stats() {
+        println!("{:>8}  {:>6}  {:>6}", stats.question, stats.groups, stats.people);
+    }
 
-fn parse_groups2(contents: &str) -> Vec<HashSet<char>> {
-    contents.split("\n\n").map(|group_str| {
-        let people = group_str.lines().collect::<Vec<&str>>();
-        ('a'..='z').filter(|c| {
-            people.iter().all(|person| person.contains(*c))
-        }).collect::<HashSet<char>>()
-    }).collect()
+    let ratios = survey.agreement_ratios();
+    for (i, ratio) in ratios.iter().enumerate() {
+        println!("Group {} ({} people) agreed on {:.1}% of its answers", i + 1, survey.groups[i].len(), ratio * 100.0);
+    }
+    let mean = ratios.iter().sum::<f64>() / ratios.len() as f64;
+    println!("Mean agreement across {} groups is {:.1}%", ratios.len(), mean * 100.0);
 }
\ No newline at end of file
@@ -0,0 +1,82 @@
+use nom::{IResult, Finish};
+use nom::branch::alt;
+use nom::bytes::complete::tag;
+use nom::character::complete::digit1;
+use nom::combinator::{all_consuming, map, map_res, opt, value};
+use nom::sequence::{preceded, terminated};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Query {
+    Any,
+    All,
+    None,
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl Query {
+    pub fn parse(s: &str) -> Result<Query, nom::error::Error<&str>> {
+        all_consuming(parse_query)(s).finish().map(|(_, query)| query)
+    }
+
+    pub fn matches(&self, answered: usize, group_size: usize) -> bool {
+        match self {
+            Query::Any => answered > 0,
+            Query::All => answered == group_size,
+            Query::None => answered == 0,
+            Query::Exactly(n) => answered == *n,
+            Query::AtLeast(n) => answered >= *n,
+        }
+    }
+}
+
+fn parse_query(s: &str) -> IResult<&str, Query> {
+    alt((
+        value(Query::Any, tag("any")),
+        value(Query::All, tag("all")),
+        value(Query::None, tag("none")),
+        map(preceded(tag("exactly "), parse_digit1), Query::Exactly),
+        map(
+            preceded(tag("at least "), terminated(parse_digit1, opt(tag(" of group")))),
+            Query::AtLeast,
+        ),
+    ))(s)
+}
+
+fn parse_digit1(s: &str) -> IResult<&str, usize> {
+    map_res(digit1, |s: &str| s.parse::<usize>())(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::query::Query;
+
+    #[test]
+    fn parses_queries() {
+        assert_eq!(Query::parse("any"), Ok(Query::Any));
+        assert_eq!(Query::parse("all"), Ok(Query::All));
+        assert_eq!(Query::parse("none"), Ok(Query::None));
+        assert_eq!(Query::parse("exactly 3"), Ok(Query::Exactly(3)));
+        assert_eq!(Query::parse("at least 2"), Ok(Query::AtLeast(2)));
+        assert_eq!(Query::parse("at least 2 of group"), Ok(Query::AtLeast(2)));
+
+        assert!(Query::parse("exactly").is_err());
+        assert!(Query::parse("exactly -1").is_err());
+        assert!(Query::parse("at most 2").is_err());
+        assert!(Query::parse("all of them").is_err());
+    }
+
+    #[test]
+    fn matches_answer_counts() {
+        assert!(Query::Any.matches(1, 3));
+        assert!(!Query::Any.matches(0, 3));
+        assert!(Query::All.matches(3, 3));
+        assert!(!Query::All.matches(2, 3));
+        assert!(Query::None.matches(0, 3));
+        assert!(!Query::None.matches(1, 3));
+        assert!(Query::Exactly(2).matches(2, 3));
+        assert!(!Query::Exactly(2).matches(3, 3));
+        assert!(Query::AtLeast(2).matches(3, 3));
+        assert!(!Query::AtLeast(2).matches(1, 3));
+    }
+}
@@ -1,18 +1,39 @@
-use std::collections::{HashMap, HashSet};
-use nom::lib::std::collections::VecDeque;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
 
 #[derive(Debug)]
 pub struct Graph {
-    edges: HashMap<String, HashSet<Edge>>,
+    edges: BTreeMap<String, BTreeSet<Edge>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CycleError {
+    pub cycle: Vec<String>,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "found a cycle: {}", self.cycle.join(" -> "))
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Visit {
+    InProgress,
+    Done,
 }
 
 impl Graph {
     pub fn new() -> Self {
         Graph {
-            edges: HashMap::new(),
+            edges: BTreeMap::new(),
         }
     }
 
+    pub fn add_node(&mut self, n: &str) {
+        self.edges.entry(n.to_owned()).or_default();
+    }
+
     pub fn add_edge(&mut self, from: &str, to: &str, quantity: i32) {
         let edge = Edge::new(to, quantity);
         match self.edges.get_mut(from) {
@@ -20,61 +41,359 @@ impl Graph {
                 tos.insert(edge);
             },
             None => {
-                let mut nodes = HashSet::new();
+                let mut nodes = BTreeSet::new();
                 nodes.insert(edge);
                 self.edges.insert(from.to_owned(), nodes);
             }
         }
     }
 
-    pub fn nodes_reachable_from(&self, n: &str) -> Option<i32> {
-        let mut nodes_to_check: VecDeque<&str> = self.edges.get(n)?.iter().map(|e| &e.value[..]).collect();
+    pub fn nodes(&self) -> BTreeSet<&str> {
+        self.edges.iter()
+            .flat_map(|(from, tos)| {
+                std::iter::once(&from[..]).chain(tos.iter().map(|e| &e.value[..]))
+            })
+            .collect()
+    }
+
+    fn edges_from(&self, n: &str) -> impl Iterator<Item = &Edge> {
+        self.edges.get(n).into_iter().flatten()
+    }
+
+    pub fn reachable_from(&self, n: &str) -> HashSet<&str> {
+        let mut nodes_to_check: VecDeque<&str> = self.edges_from(n).map(|e| &e.value[..]).collect();
         let mut reachable_nodes: HashSet<&str> = nodes_to_check.iter().cloned().collect();
-        while !nodes_to_check.is_empty() {
-            let candidate = nodes_to_check.pop_front().unwrap();
-            if let Some(edges) = self.edges.get(candidate) {
-                for edge in edges {
-                    if !reachable_nodes.contains(&edge.value[..]) {
-                        reachable_nodes.insert(&edge.value);
-                        nodes_to_check.push_back(&edge.value);
-                    }
+        while let Some(candidate) = nodes_to_check.pop_front() {
+            for edge in self.edges_from(candidate) {
+                if reachable_nodes.insert(&edge.value) {
+                    nodes_to_check.push_back(&edge.value);
                 }
             }
         }
 
-        Some(reachable_nodes.len() as i32)
+        reachable_nodes
     }
 
-    pub fn inverted(&self) -> Graph {
-        let mut new_graph = Graph::new();
-
+    pub fn ancestors(&self, n: &str) -> BTreeSet<&str> {
+        let mut parents: HashMap<&str, Vec<&str>> = HashMap::new();
         for (from, tos) in self.edges.iter() {
             for to in tos {
-                new_graph.add_edge(&to.value, from, to.quantity);
+                parents.entry(&to.value[..]).or_default().push(from);
             }
         }
 
-        new_graph
+        let mut nodes_to_check = vec![n];
+        let mut ancestors = BTreeSet::new();
+        while let Some(candidate) = nodes_to_check.pop() {
+            for parent in parents.get(candidate).into_iter().flatten() {
+                if ancestors.insert(*parent) {
+                    nodes_to_check.push(parent);
+                }
+            }
+        }
+
+        ancestors
     }
 
-    pub fn count_nodes(&self, start: &str) -> i32 {
-        match self.edges.get(start) {
-            Some(edges) => {
-                edges.iter().map(|e| e.quantity * (1 + self.count_nodes(&e.value))).sum()
-            },
-            None => 0,
+    pub fn count_nodes(&self, start: &str) -> Result<i64, CycleError> {
+        let mut totals = HashMap::new();
+        let mut visits = HashMap::new();
+        let mut stack = Vec::new();
+        self.count_nodes_memoized(start, &mut totals, &mut visits, &mut stack)
+    }
+
+    fn count_nodes_memoized<'a>(
+        &'a self,
+        n: &'a str,
+        totals: &mut HashMap<&'a str, i64>,
+        visits: &mut HashMap<&'a str, Visit>,
+        stack: &mut Vec<&'a str>,
+    ) -> Result<i64, CycleError> {
+        if let Some(total) = totals.get(n) {
+            return Ok(*total);
+        }
+        if visits.get(n) == Some(&Visit::InProgress) {
+            return Err(cycle_error(stack, n));
+        }
+
+        visits.insert(n, Visit::InProgress);
+        stack.push(n);
+
+        let mut total = 0;
+        for edge in self.edges_from(n) {
+            let contents = self.count_nodes_memoized(&edge.value, totals, visits, stack)?;
+            total += edge.quantity as i64 * (1 + contents);
+        }
+
+        stack.pop();
+        visits.insert(n, Visit::Done);
+        totals.insert(n, total);
+        Ok(total)
+    }
+
+    pub fn find_cycle(&self) -> Option<Vec<String>> {
+        self.topological_order().err().map(|err| err.cycle)
+    }
+
+    pub fn topological_order(&self) -> Result<Vec<&str>, CycleError> {
+        let mut visits = HashMap::new();
+        let mut stack = Vec::new();
+        let mut order = Vec::new();
+
+        for n in self.nodes() {
+            self.visit_topological(n, &mut visits, &mut stack, &mut order)?;
+        }
+
+        order.reverse();
+        Ok(order)
+    }
+
+    fn visit_topological<'a>(
+        &'a self,
+        n: &'a str,
+        visits: &mut HashMap<&'a str, Visit>,
+        stack: &mut Vec<&'a str>,
+        order: &mut Vec<&'a str>,
+    ) -> Result<(), CycleError> {
+        match visits.get(n) {
+            Some(Visit::Done) => return Ok(()),
+            Some(Visit::InProgress) => return Err(cycle_error(stack, n)),
+            None => {}
+        }
+
+        visits.insert(n, Visit::InProgress);
+        stack.push(n);
+
+        for edge in self.edges_from(n) {
+            self.visit_topological(&edge.value, visits, stack, order)?;
+        }
+
+        stack.pop();
+        visits.insert(n, Visit::Done);
+        order.push(n);
+        Ok(())
+    }
+
+    pub fn paths(&self, from: &str, to: &str) -> Result<Vec<Vec<&str>>, CycleError> {
+        let from = match self.nodes().get(from) {
+            Some(n) => *n,
+            None => return Ok(vec![]),
+        };
+
+        // a cycle anywhere the search can get to would mean it never finishes, but cycles
+        // elsewhere in the graph don't matter
+        let mut visits = HashMap::new();
+        self.visit_topological(from, &mut visits, &mut Vec::new(), &mut Vec::new())?;
+
+        let mut paths = Vec::new();
+        let mut path = vec![from];
+        self.collect_paths(to, &mut path, &mut paths);
+        Ok(paths)
+    }
+
+    fn collect_paths<'a>(&'a self, to: &str, path: &mut Vec<&'a str>, paths: &mut Vec<Vec<&'a str>>) {
+        let current = path[path.len() - 1];
+        if current == to {
+            paths.push(path.clone());
+            return;
+        }
+
+        for edge in self.edges_from(current) {
+            path.push(&edge.value);
+            self.collect_paths(to, path, paths);
+            path.pop();
         }
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnknownNode(pub String);
+
+impl fmt::Display for UnknownNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "there are no rules for {} bags", self.0)
+    }
+}
+
+pub enum Restriction<'a> {
+    All,
+    From(&'a str),
+    To(&'a str),
+}
+
+impl Graph {
+    pub fn to_dot(&self, restriction: Restriction) -> Result<String, UnknownNode> {
+        if let Restriction::From(n) | Restriction::To(n) = restriction {
+            if !self.nodes().contains(n) {
+                return Err(UnknownNode(n.to_owned()));
+            }
+        }
+
+        let included: BTreeSet<&str> = match restriction {
+            Restriction::All => self.nodes(),
+            Restriction::From(n) => self.reachable_from(n).into_iter().chain(std::iter::once(n)).collect(),
+            Restriction::To(n) => self.ancestors(n).into_iter().chain(std::iter::once(n)).collect(),
+        };
+
+        let mut dot = String::from("digraph bags {\n");
+        for n in self.nodes() {
+            if included.contains(n) {
+                dot.push_str(&format!("    {:?};\n", n));
+            }
+        }
+        for (from, tos) in self.edges.iter() {
+            if !included.contains(&from[..]) {
+                continue;
+            }
+            for to in tos {
+                if included.contains(&to.value[..]) {
+                    dot.push_str(&format!("    {:?} -> {:?} [label=\"{}\"];\n", from, to.value, to.quantity));
+                }
+            }
+        }
+        dot.push_str("}\n");
+        Ok(dot)
+    }
+}
+
+fn cycle_error(stack: &[&str], n: &str) -> CycleError {
+    let start = stack.iter().position(|m| *m == n).unwrap_or(0);
+    let mut cycle: Vec<String> = stack[start..].iter().map(|m| m.to_string()).collect();
+    cycle.push(n.to_owned());
+    CycleError { cycle }
+}
+
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 struct Edge {
-    quantity: i32,
     value: String,
+    quantity: i32,
 }
 
 impl Edge {
     fn new(value: &str, quantity: i32) -> Self {
         Edge { value: value.to_owned(), quantity }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use crate::graph::{CycleError, Graph, Restriction, UnknownNode};
+    use crate::rules::BagRules;
+
+    const EXAMPLE: &str = "\
+light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.";
+
+    fn example() -> Graph {
+        BagRules::parse(EXAMPLE).unwrap().to_graph()
+    }
+
+    fn cyclic() -> Graph {
+        let mut graph = Graph::new();
+        graph.add_edge("a", "b", 1);
+        graph.add_edge("b", "c", 2);
+        graph.add_edge("x", "y", 1);
+        graph.add_edge("y", "z", 1);
+        graph.add_edge("z", "x", 1);
+        graph
+    }
+
+    #[test]
+    fn ancestors_and_reachable() {
+        let graph = example();
+        let ancestors = graph.ancestors("shiny gold");
+        assert_eq!(ancestors, ["bright white", "dark orange", "light red", "muted yellow"].iter().cloned().collect());
+        assert_eq!(graph.ancestors("light red"), BTreeSet::new());
+        assert_eq!(graph.reachable_from("shiny gold").len(), 4);
+    }
+
+    #[test]
+    fn counts_nodes() {
+        assert_eq!(example().count_nodes("shiny gold"), Ok(32));
+        assert_eq!(example().count_nodes("faded blue"), Ok(0));
+
+        let deep = BagRules::parse("\
+shiny gold bags contain 2 dark red bags.
+dark red bags contain 2 dark orange bags.
+dark orange bags contain 2 dark yellow bags.
+dark yellow bags contain 2 dark green bags.
+dark green bags contain 2 dark blue bags.
+dark blue bags contain 2 dark violet bags.
+dark violet bags contain no other bags.").unwrap().to_graph();
+        assert_eq!(deep.count_nodes("shiny gold"), Ok(126));
+    }
+
+    #[test]
+    fn orders_topologically() {
+        let graph = example();
+        let order = graph.topological_order().unwrap();
+        assert_eq!(order.len(), graph.nodes().len());
+
+        let position = |n: &str| order.iter().position(|m| *m == n).unwrap();
+        for (from, to) in [("light red", "bright white"), ("muted yellow", "shiny gold"), ("shiny gold", "dotted black")].iter() {
+            assert!(position(from) < position(to), "{} should come before {}", from, to);
+        }
+    }
+
+    #[test]
+    fn reports_cycles() {
+        let graph = cyclic();
+        let cycle = vec!["x".to_owned(), "y".to_owned(), "z".to_owned(), "x".to_owned()];
+        assert_eq!(graph.find_cycle(), Some(cycle.clone()));
+        assert_eq!(graph.count_nodes("y").err().map(|err| err.cycle.len()), Some(4));
+        assert_eq!(graph.count_nodes("a"), Ok(3));
+        assert_eq!(example().find_cycle(), None);
+
+        assert_eq!(CycleError { cycle }.to_string(), "found a cycle: x -> y -> z -> x");
+    }
+
+    #[test]
+    fn paths_ignore_unrelated_cycles() {
+        let graph = cyclic();
+        assert_eq!(graph.paths("a", "c"), Ok(vec![vec!["a", "b", "c"]]));
+        assert!(graph.paths("x", "z").is_err());
+        assert_eq!(graph.paths("q", "c"), Ok(vec![]));
+
+        let example = example();
+        let paths = example.paths("light red", "shiny gold").unwrap();
+        assert_eq!(paths, vec![
+            vec!["light red", "bright white", "shiny gold"],
+            vec!["light red", "muted yellow", "shiny gold"],
+        ]);
+    }
+
+    #[test]
+    fn restricted_dot_output() {
+        let graph = example();
+        assert_eq!(graph.to_dot(Restriction::From("shiny gold")), Ok("\
+digraph bags {
+    \"dark olive\";
+    \"dotted black\";
+    \"faded blue\";
+    \"shiny gold\";
+    \"vibrant plum\";
+    \"dark olive\" -> \"dotted black\" [label=\"4\"];
+    \"dark olive\" -> \"faded blue\" [label=\"3\"];
+    \"shiny gold\" -> \"dark olive\" [label=\"1\"];
+    \"shiny gold\" -> \"vibrant plum\" [label=\"2\"];
+    \"vibrant plum\" -> \"dotted black\" [label=\"6\"];
+    \"vibrant plum\" -> \"faded blue\" [label=\"5\"];
+}
+".to_owned()));
+
+        let to_bright_white = graph.to_dot(Restriction::To("bright white")).unwrap();
+        assert_eq!(to_bright_white.lines().filter(|line| line.contains("->")).count(), 2);
+        assert!(!to_bright_white.contains("shiny gold"));
+
+        assert_eq!(graph.to_dot(Restriction::From("no such")), Err(UnknownNode("no such".to_owned())));
+        assert_eq!(graph.to_dot(Restriction::To("no such")), Err(UnknownNode("no such".to_owned())));
+    }
+}
@@ -1,87 +1,103 @@
 mod graph;
+mod rules;
 
 use std::env;
 use std::fs;
-use crate::graph::Graph;
-use nom::{IResult, Finish};
-use nom::bytes::complete::{tag};
-use nom::sequence::{separated_pair, terminated};
-use nom::character::complete::{alpha1, space1, digit1, newline, char};
-use nom::combinator::{map, recognize, value, map_res, all_consuming};
-use nom::multi::separated_list1;
-use nom::branch::alt;
+use crate::graph::{Graph, Restriction};
+use crate::rules::BagRules;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
     let input_path = &args[1];
-    println!("Reading input from {}", input_path);
-
     let contents = fs::read_to_string(input_path)
         .expect("Something went wrong reading the file");
-    let graph = parse_graph(&contents)
+    let rules = BagRules::parse(&contents)
         .unwrap();
 
+    let errors = rules.validate();
+    if !errors.is_empty() {
+        eprintln!("The bag rules are invalid:");
+        for error in errors {
+            eprintln!("  {}", error);
+        }
+        return;
+    }
+
+    let graph = rules.to_graph();
+
+    // the DOT output goes to stdout on its own so it can be piped straight into dot
+    if let Some(restriction) = dot_restriction(&args[2..]) {
+        match graph.to_dot(restriction) {
+            Ok(dot) => print!("{}", dot),
+            Err(err) => eprintln!("Could not draw the bag graph: {}", err),
+        }
+        return;
+    }
+
+    println!("Reading input from {}", input_path);
+
+    if let Some(cycle) = graph.find_cycle() {
+        println!("The bag rules contain a cycle: {}", cycle.join(" -> "));
+        return;
+    }
+
     part1(&graph);
     part2(&graph);
+
+    let mut options = args[2..].iter();
+    while let Some(option) = options.next() {
+        match &option[..] {
+            "--order" => print_order(&graph),
+            "--rules" => println!("{}", rules),
+            "--paths" => {
+                let from = options.next().expect("--paths needs a bag colour to start from");
+                let to = options.next().expect("--paths needs a bag colour to end at");
+                print_paths(&graph, from, to);
+            }
+            _ => panic!("Unknown option {}", option),
+        }
+    }
 }
 
 fn part1(graph: &Graph) {
-    let result = graph.nodes_reachable_from("shiny gold")
-        .expect("Could not find any nodes reachable from shiny gold");
+    let result = graph.ancestors("shiny gold").len();
     println!("There are {} kinds of bags that can contain a shiny gold bag.", result);
 }
 
 fn part2(graph: &Graph) {
-    let inverted_graph = graph.inverted();
-
-    let result = inverted_graph.count_nodes("shiny gold");
-    println!("A shiny gold bag contains {} other bags.", result);
-}
-
-fn parsed_digit1(s: &str) -> IResult<&str, i32> {
-    map_res(digit1, |s: &str| s.parse::<i32>())(s)
+    let result = graph.count_nodes("shiny gold")
+        .expect("Could not count the bags inside a shiny gold bag");
+    let kinds = graph.reachable_from("shiny gold").len();
+    println!("A shiny gold bag contains {} other bags of {} kinds.", result, kinds);
 }
 
-fn bag_desc(s: &str) -> IResult<&str, &str> {
-    recognize(separated_pair(alpha1, space1, alpha1))(s)
+fn print_order(graph: &Graph) {
+    let order = graph.topological_order()
+        .expect("Could not order the bag rules");
+    for (i, node) in order.iter().enumerate() {
+        println!("{:>4}. {}", i + 1, node);
+    }
 }
 
-fn graph_parser(s: &str) -> IResult<&str, Graph> {
-    let single_bag_desc = terminated(separated_pair(value(1, tag("1")), space1, bag_desc), tag(" bag"));
-    let multi_bag_desc = terminated(separated_pair(parsed_digit1, space1, bag_desc), tag(" bags"));
-    let counted_bag_desc = alt((
-        single_bag_desc,
-        multi_bag_desc,
-    ));
-    let graph_line = terminated(separated_pair(
-        bag_desc,
-        tag(" bags contain "),
-        alt((
-            value(vec![], tag("no other bags")),
-            separated_list1(
-                tag(", "),
-                counted_bag_desc,
-            ),
-        )),
-    ), char('.'));
-
-    map(separated_list1(
-        newline,
-        graph_line,
-    ), |lines| {
-        let mut graph = Graph::new();
-
-        for (from_desc, tos) in lines {
-            for (quantity, to_desc) in tos {
-                graph.add_edge(to_desc, from_desc, quantity);
-            }
+fn dot_restriction(options: &[String]) -> Option<Restriction<'_>> {
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        match &option[..] {
+            "--dot" => return Some(Restriction::All),
+            "--dot-from" => return Some(Restriction::From(options.next().expect("--dot-from needs a bag colour"))),
+            "--dot-to" => return Some(Restriction::To(options.next().expect("--dot-to needs a bag colour"))),
+            _ => {}
         }
-
-        graph
-    })(s)
+    }
+    None
 }
 
-fn parse_graph(contents: &str) -> Result<Graph, nom::error::Error<&str>> {
-    all_consuming(graph_parser)(contents).finish().map(|(_, graph)| graph)
-}
+fn print_paths(graph: &Graph, from: &str, to: &str) {
+    let paths = graph.paths(from, to)
+        .expect("Could not find paths between bags");
+    println!("There are {} ways a {} bag can contain a {} bag:", paths.len(), from, to);
+    for path in paths {
+        println!("  {}", path.join(" > "));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,209 @@
+use std::collections::HashSet;
+use std::fmt;
+use nom::{IResult, Finish};
+use nom::bytes::complete::tag;
+use nom::sequence::{separated_pair, terminated};
+use nom::character::complete::{alpha1, space1, digit1, newline, char};
+use nom::combinator::{map, recognize, value, map_res, all_consuming, verify};
+use nom::multi::separated_list1;
+use nom::branch::alt;
+use crate::graph::Graph;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Rule {
+    pub color: String,
+    pub contents: Vec<(i32, String)>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BagRules {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RuleError {
+    DuplicateDefinition(String),
+    UndefinedColor { color: String, referenced_by: String },
+    SelfContainment(String),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::DuplicateDefinition(color) => {
+                write!(f, "{} bags are defined more than once", color)
+            }
+            RuleError::UndefinedColor { color, referenced_by } => {
+                write!(f, "{} bags contain {} bags, which are never defined", referenced_by, color)
+            }
+            RuleError::SelfContainment(color) => write!(f, "{} bags contain themselves", color),
+        }
+    }
+}
+
+impl BagRules {
+    pub fn parse(s: &str) -> Result<BagRules, nom::error::Error<&str>> {
+        all_consuming(rules_parser)(s).finish().map(|(_, rules)| BagRules { rules })
+    }
+
+    pub fn validate(&self) -> Vec<RuleError> {
+        let mut errors = Vec::new();
+
+        let mut defined = HashSet::new();
+        for rule in &self.rules {
+            if !defined.insert(&rule.color[..]) {
+                errors.push(RuleError::DuplicateDefinition(rule.color.clone()));
+            }
+        }
+
+        for rule in &self.rules {
+            for (_, color) in &rule.contents {
+                if *color == rule.color {
+                    errors.push(RuleError::SelfContainment(rule.color.clone()));
+                } else if !defined.contains(&color[..]) {
+                    errors.push(RuleError::UndefinedColor {
+                        color: color.clone(),
+                        referenced_by: rule.color.clone(),
+                    });
+                }
+            }
+        }
+
+        errors
+    }
+
+    pub fn to_graph(&self) -> Graph {
+        let mut graph = Graph::new();
+
+        for rule in &self.rules {
+            graph.add_node(&rule.color);
+            for (quantity, color) in &rule.contents {
+                graph.add_edge(&rule.color, color, *quantity);
+            }
+        }
+
+        graph
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bags contain ", self.color)?;
+        if self.contents.is_empty() {
+            return write!(f, "no other bags.");
+        }
+
+        for (i, (quantity, color)) in self.contents.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            let noun = if *quantity == 1 { "bag" } else { "bags" };
+            write!(f, "{} {} {}", quantity, color, noun)?;
+        }
+        write!(f, ".")
+    }
+}
+
+impl fmt::Display for BagRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, rule) in self.rules.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", rule)?;
+        }
+        Ok(())
+    }
+}
+
+// only the spellings Display writes back out are accepted, so that parsing and printing rules
+// round-trips exactly: "1 x bag" but "2 x bags", and no leading zeros
+fn plural_count(s: &str) -> IResult<&str, i32> {
+    map_res(
+        verify(digit1, |digits: &str| digits != "1" && !digits.starts_with('0')),
+        |digits: &str| digits.parse::<i32>(),
+    )(s)
+}
+
+fn bag_desc(s: &str) -> IResult<&str, &str> {
+    recognize(separated_pair(alpha1, space1, alpha1))(s)
+}
+
+fn rules_parser(s: &str) -> IResult<&str, Vec<Rule>> {
+    let single_bag_desc = terminated(separated_pair(value(1, tag("1")), space1, bag_desc), tag(" bag"));
+    let multi_bag_desc = terminated(separated_pair(plural_count, space1, bag_desc), tag(" bags"));
+    let counted_bag_desc = alt((
+        single_bag_desc,
+        multi_bag_desc,
+    ));
+    let rule_line = terminated(separated_pair(
+        bag_desc,
+        tag(" bags contain "),
+        alt((
+            value(vec![], tag("no other bags")),
+            separated_list1(
+                tag(", "),
+                counted_bag_desc,
+            ),
+        )),
+    ), char('.'));
+
+    separated_list1(
+        newline,
+        map(rule_line, |(color, contents): (&str, Vec<(i32, &str)>)| Rule {
+            color: color.to_owned(),
+            contents: contents.into_iter().map(|(n, c)| (n, c.to_owned())).collect(),
+        }),
+    )(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::{BagRules, RuleError};
+
+    const EXAMPLE: &str = "\
+light red bags contain 1 bright white bag, 2 muted yellow bags.
+dark orange bags contain 3 bright white bags, 4 muted yellow bags.
+bright white bags contain 1 shiny gold bag.
+muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.
+shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.
+dark olive bags contain 3 faded blue bags, 4 dotted black bags.
+vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
+faded blue bags contain no other bags.
+dotted black bags contain no other bags.";
+
+    #[test]
+    fn round_trips() {
+        let rules = BagRules::parse(EXAMPLE).unwrap();
+        assert_eq!(rules.to_string(), EXAMPLE);
+        assert_eq!(BagRules::parse(&rules.to_string()), Ok(rules));
+
+        let big = "pale red bags contain 10 dim tan bags, 1 dim gold bag.\ndim tan bags contain no other bags.";
+        assert_eq!(BagRules::parse(big).unwrap().to_string(), big);
+    }
+
+    #[test]
+    fn rejects_spellings_that_do_not_round_trip() {
+        assert!(BagRules::parse("light red bags contain 1 bright white bags.").is_err());
+        assert!(BagRules::parse("light red bags contain 2 bright white bag.").is_err());
+        assert!(BagRules::parse("light red bags contain 02 bright white bags.").is_err());
+        assert!(BagRules::parse("light red bags contain 0 bright white bags.").is_err());
+    }
+
+    #[test]
+    fn validates_rules() {
+        let rules = BagRules::parse(EXAMPLE).unwrap();
+        assert_eq!(rules.validate(), vec![]);
+
+        let rules = BagRules::parse("\
+light red bags contain 1 bright white bag.
+bright white bags contain 2 light red bags, 1 bright white bag, 3 shiny gold bags.
+light red bags contain no other bags.").unwrap();
+        assert_eq!(rules.validate(), vec![
+            RuleError::DuplicateDefinition("light red".to_owned()),
+            RuleError::SelfContainment("bright white".to_owned()),
+            RuleError::UndefinedColor { color: "shiny gold".to_owned(), referenced_by: "bright white".to_owned() },
+        ]);
+        assert_eq!(rules.validate()[2].to_string(), "bright white bags contain shiny gold bags, which are never defined");
+    }
+}
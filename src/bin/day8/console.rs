@@ -6,62 +6,136 @@ use nom::combinator::{map_res, map, all_consuming};
 use nom::bytes::complete::tag;
 use nom::multi::separated_list0;
 use nom::lib::std::collections::HashSet;
+use std::borrow::Cow;
+use std::fmt;
 
-pub struct Console<'a> {
-    insts: &'a Vec<Instruction>,
+pub enum Flow {
+    Next,
+    Jump(i32),
+}
+
+pub trait Operation {
+    const REGISTERS: usize;
+
+    fn execute(&self, registers: &mut [i32]) -> Flow;
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TraceEntry {
+    pub pc: usize,
+    pub registers: Vec<i32>,
+}
+
+pub struct Console<'a, I: Operation + Clone = Instruction> {
+    insts: Cow<'a, [I]>,
     pc: usize,
-    acc: i32,
+    registers: Vec<i32>,
     already_executed: HashSet<usize>,
+    steps: usize,
+    step_limit: Option<usize>,
+    trace: Option<Vec<TraceEntry>>,
 }
 
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum RunResult {
     Terminated,
-    InfiniteLoop,
+    LoopDetected { pc: usize },
+    OutOfBounds { pc: usize, target: isize },
+    StepLimitExceeded { steps: usize },
 }
 
-impl<'a> Console<'a> {
-    pub fn new(insts: &'a Vec<Instruction>) -> Console<'a> {
+impl<'a, I: Operation + Clone> Console<'a, I> {
+    pub fn new(insts: &'a [I]) -> Console<'a, I> {
         Console {
-            insts,
+            insts: Cow::Borrowed(insts),
             pc: 0,
-            acc: 0,
+            registers: vec![0; I::REGISTERS],
             already_executed: HashSet::new(),
+            steps: 0,
+            step_limit: None,
+            trace: None,
         }
     }
 
-    pub fn step(&mut self) {
-        self.already_executed.insert(self.pc);
+    pub fn with_step_limit(mut self, limit: usize) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
 
-        match &self.insts[self.pc] {
-            Instruction::Nop(_) => self.pc += 1,
-            Instruction::Acc(n) => {
-                self.acc += n;
-                self.pc += 1;
-            },
-            Instruction::Jmp(offset) => {
-                if *offset < 0 {
-                    self.pc -= offset.abs() as usize
-                } else {
-                    self.pc += *offset as usize
-                }
-            }
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Vec::new());
+        self
+    }
+
+    pub fn step(&mut self) -> Option<RunResult> {
+        if self.pc == self.insts.len() {
+            return Some(RunResult::Terminated);
         }
+
+        let pc = self.pc;
+        self.already_executed.insert(pc);
+        self.steps += 1;
+
+        let offset = match self.insts[pc].execute(&mut self.registers) {
+            Flow::Next => 1,
+            Flow::Jump(offset) => offset,
+        };
+
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceEntry { pc, registers: self.registers.clone() });
+        }
+
+        let target = pc as isize + offset as isize;
+        if target < 0 || target > self.insts.len() as isize {
+            return Some(RunResult::OutOfBounds { pc, target });
+        }
+
+        self.pc = target as usize;
+        if self.pc == self.insts.len() {
+            return Some(RunResult::Terminated);
+        }
+
+        None
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn instructions(&self) -> &[I] {
+        &self.insts
+    }
+
+    pub fn patch(&mut self, pc: usize, inst: I) {
+        self.insts.to_mut()[pc] = inst;
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.already_executed.contains(&self.pc)
     }
 
     pub fn acc(&self) -> i32 {
-        self.acc
+        self.registers[0]
+    }
+
+    pub fn trace(&self) -> &[TraceEntry] {
+        self.trace.as_deref().unwrap_or(&[])
     }
 
     pub fn run(&mut self) -> RunResult {
         loop {
-            self.step();
+            if let Some(limit) = self.step_limit {
+                if self.steps >= limit {
+                    return RunResult::StepLimitExceeded { steps: self.steps };
+                }
+            }
 
-            if self.already_executed.contains(&self.pc) {
-                return RunResult::InfiniteLoop;
+            if let Some(result) = self.step() {
+                return result;
             }
 
-            if self.pc == self.insts.len() {
-                return RunResult::Terminated;
+            if self.is_looping() {
+                return RunResult::LoopDetected { pc: self.pc };
             }
         }
     }
@@ -74,10 +148,135 @@ pub enum Instruction {
     Jmp(i32),
 }
 
+impl Operation for Instruction {
+    const REGISTERS: usize = 1;
+
+    fn execute(&self, registers: &mut [i32]) -> Flow {
+        match self {
+            Instruction::Nop(_) => Flow::Next,
+            Instruction::Acc(n) => {
+                registers[0] += n;
+                Flow::Next
+            }
+            Instruction::Jmp(offset) => Flow::Jump(*offset),
+        }
+    }
+}
+
+impl Instruction {
+    pub fn flipped(&self) -> Option<Instruction> {
+        match self {
+            Instruction::Nop(n) => Some(Instruction::Jmp(*n)),
+            Instruction::Jmp(n) => Some(Instruction::Nop(*n)),
+            Instruction::Acc(_) => None,
+        }
+    }
+
+    pub fn successor(&self, pc: usize) -> isize {
+        match self {
+            Instruction::Jmp(offset) => pc as isize + *offset as isize,
+            _ => pc as isize + 1,
+        }
+    }
+
+    fn acc_delta(&self) -> i32 {
+        match self {
+            Instruction::Acc(n) => *n,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Repair {
+    pub pc: usize,
+    pub replacement: Instruction,
+    pub acc: i32,
+}
+
+pub fn repair(insts: &[Instruction]) -> Option<Repair> {
+    repair_candidates(insts).into_iter().next()
+}
+
+// Works backwards from the end of the program to find every instruction that leads to termination,
+// then walks the original program once looking for a flip that lands on one of them.
+// A program that already terminates has nothing to repair, so it gets no candidates: flipping an
+// instruction it never reaches would trivially "fix" it, and the walk above wouldn't see those.
+pub fn repair_candidates(insts: &[Instruction]) -> Vec<Repair> {
+    let len = insts.len();
+
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); len + 1];
+    for (pc, inst) in insts.iter().enumerate() {
+        let target = inst.successor(pc);
+        if target >= 0 && target <= len as isize {
+            predecessors[target as usize].push(pc);
+        }
+    }
+
+    // acc_to_end[pc] is the accumulator gained running from pc to termination, if it terminates
+    let mut acc_to_end: Vec<Option<i32>> = vec![None; len + 1];
+    acc_to_end[len] = Some(0);
+    let mut to_visit = vec![len];
+    while let Some(target) = to_visit.pop() {
+        let acc = acc_to_end[target].unwrap();
+        for pc in &predecessors[target] {
+            if acc_to_end[*pc].is_none() {
+                acc_to_end[*pc] = Some(acc + insts[*pc].acc_delta());
+                to_visit.push(*pc);
+            }
+        }
+    }
+
+    let mut candidates = Vec::new();
+    let mut visited = vec![false; len];
+    let mut pc = 0;
+    let mut acc = 0;
+    while pc < len && !visited[pc] {
+        visited[pc] = true;
+
+        let inst = &insts[pc];
+        if let Some(replacement) = inst.flipped() {
+            let target = replacement.successor(pc);
+            if target >= 0 && target <= len as isize {
+                if let Some(remaining) = acc_to_end[target as usize] {
+                    candidates.push(Repair { pc, replacement, acc: acc + remaining });
+                }
+            }
+        }
+
+        acc += inst.acc_delta();
+        let next = inst.successor(pc);
+        if next < 0 {
+            break;
+        }
+        pc = next as usize;
+    }
+
+    if pc == len {
+        return Vec::new();
+    }
+
+    candidates
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Nop(n) => write!(f, "nop {:+}", n),
+            Instruction::Acc(n) => write!(f, "acc {:+}", n),
+            Instruction::Jmp(n) => write!(f, "jmp {:+}", n),
+        }
+    }
+}
+
 type ParseError<'a> = nom::error::Error<&'a str>;
 
 impl Instruction {
-    pub fn from_lines(s: &str) -> Result<Vec<Instruction>, ParseError> {
+    pub fn parse(s: &str) -> Result<Instruction, ParseError<'_>> {
+        all_consuming(instruction_parser)(s).finish().map(|(_, inst)| inst)
+    }
+
+    pub fn from_lines(s: &str) -> Result<Vec<Instruction>, ParseError<'_>> {
         all_consuming(instruction_list_parser)(s).finish().map(|(_, insts)| insts)
     }
 }
@@ -103,4 +302,139 @@ fn instruction_parser(s: &str) -> IResult<&str, Instruction> {
 
 fn instruction_list_parser(s: &str) -> IResult<&str, Vec<Instruction>> {
     separated_list0(newline, instruction_parser)(s)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::console::{Console, Flow, Instruction, Operation, Repair, RunResult, TraceEntry, repair, repair_candidates};
+
+    const EXAMPLE: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    fn run(program: &str) -> (RunResult, i32) {
+        let insts = Instruction::from_lines(program).unwrap();
+        let mut console = Console::new(&insts);
+        let result = console.run();
+        (result, console.acc())
+    }
+
+    #[test]
+    fn run_results() {
+        assert_eq!(run(EXAMPLE), (RunResult::LoopDetected { pc: 1 }, 5));
+        assert_eq!(run("nop +0\nacc +3\njmp +1"), (RunResult::Terminated, 3));
+        assert_eq!(run("acc +1\njmp +5"), (RunResult::OutOfBounds { pc: 1, target: 6 }, 1));
+        assert_eq!(run("acc +1\nacc +2\njmp -3"), (RunResult::OutOfBounds { pc: 2, target: -1 }, 3));
+        assert_eq!(run("jmp +0"), (RunResult::LoopDetected { pc: 0 }, 0));
+    }
+
+    #[test]
+    fn step_limit() {
+        let insts = Instruction::from_lines("acc +1\nacc +1\nacc +1\nacc +1").unwrap();
+        let mut console = Console::new(&insts).with_step_limit(3);
+        assert_eq!(console.run(), RunResult::StepLimitExceeded { steps: 3 });
+        assert_eq!(console.acc(), 3);
+
+        let mut console = Console::new(&insts).with_step_limit(4);
+        assert_eq!(console.run(), RunResult::Terminated);
+    }
+
+    #[test]
+    fn trace_records_every_step() {
+        let insts = Instruction::from_lines("acc +2\njmp +2\nacc +100\nacc -1").unwrap();
+        let mut console = Console::new(&insts).with_trace();
+        assert_eq!(console.run(), RunResult::Terminated);
+        assert_eq!(console.trace(), &[
+            TraceEntry { pc: 0, registers: vec![2] },
+            TraceEntry { pc: 1, registers: vec![2] },
+            TraceEntry { pc: 3, registers: vec![1] },
+        ][..]);
+
+        let mut untraced = Console::new(&insts);
+        untraced.run();
+        assert!(untraced.trace().is_empty());
+    }
+
+    #[derive(Debug, Clone)]
+    enum Registers {
+        Add(usize, i32),
+        AddFrom(usize, usize),
+        JumpIfPositive(usize, i32),
+    }
+
+    impl Operation for Registers {
+        const REGISTERS: usize = 3;
+
+        fn execute(&self, registers: &mut [i32]) -> Flow {
+            match self {
+                Registers::Add(r, n) => registers[*r] += n,
+                Registers::AddFrom(r, from) => registers[*r] += registers[*from],
+                Registers::JumpIfPositive(r, offset) if registers[*r] > 0 => return Flow::Jump(*offset),
+                Registers::JumpIfPositive(_, _) => {}
+            }
+            Flow::Next
+        }
+    }
+
+    #[test]
+    fn runs_other_instruction_sets() {
+        let insts = vec![
+            Registers::Add(1, 5),
+            Registers::AddFrom(0, 1),
+            Registers::JumpIfPositive(0, 2),
+            Registers::Add(0, 100),
+            Registers::Add(2, 7),
+            Registers::JumpIfPositive(2, -5),
+        ];
+        let mut console = Console::new(&insts).with_trace();
+        assert_eq!(console.run(), RunResult::LoopDetected { pc: 0 });
+        assert_eq!(console.acc(), 5);
+        assert_eq!(console.trace().last(), Some(&TraceEntry { pc: 5, registers: vec![5, 5, 7] }));
+        assert_eq!(console.trace().iter().map(|entry| entry.pc).collect::<Vec<usize>>(), vec![0, 1, 2, 4, 5]);
+    }
+
+    // the original search: flip every instruction in turn and rerun the whole program
+    fn brute_force_repairs(insts: &[Instruction]) -> Vec<Repair> {
+        insts.iter().enumerate()
+            .filter_map(|(pc, inst)| {
+                let replacement = inst.flipped()?;
+                let mut console = Console::new(insts);
+                console.patch(pc, replacement.clone());
+                match console.run() {
+                    RunResult::Terminated => Some(Repair { pc, replacement, acc: console.acc() }),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    fn assert_repairs(program: &str, expected: &[Repair]) {
+        let insts = Instruction::from_lines(program).unwrap();
+        let mut candidates = repair_candidates(&insts);
+        candidates.sort_by_key(|candidate| candidate.pc);
+        assert_eq!(candidates, expected);
+        assert_eq!(brute_force_repairs(&insts), expected);
+        assert_eq!(repair(&insts).is_some(), !expected.is_empty());
+    }
+
+    #[test]
+    fn repairs_match_brute_force() {
+        assert_repairs(EXAMPLE, &[Repair { pc: 7, replacement: Instruction::Nop(-4), acc: 8 }]);
+        assert_repairs("jmp +0\nacc +1\njmp -1", &[]);
+        assert_repairs("nop +3\njmp -1\nacc +1\nacc +2", &[
+            Repair { pc: 0, replacement: Instruction::Jmp(3), acc: 2 },
+            Repair { pc: 1, replacement: Instruction::Nop(-1), acc: 3 },
+        ]);
+        assert_repairs("acc +1\nnop +2\njmp -3", &[
+            Repair { pc: 1, replacement: Instruction::Jmp(2), acc: 1 },
+            Repair { pc: 2, replacement: Instruction::Nop(-3), acc: 1 },
+        ]);
+        assert_repairs("nop -1\njmp +0", &[Repair { pc: 1, replacement: Instruction::Nop(0), acc: 0 }]);
+    }
+
+    #[test]
+    fn terminating_programs_need_no_repair() {
+        let insts = Instruction::from_lines("nop +0\nacc +1\njmp +2\njmp -3").unwrap();
+        assert_eq!(Console::new(&insts).run(), RunResult::Terminated);
+        assert!(repair_candidates(&insts).is_empty());
+        assert_eq!(repair(&insts), None);
+    }
+}
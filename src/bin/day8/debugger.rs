@@ -0,0 +1,205 @@
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+use crate::console::{Console, Instruction, RunResult};
+
+pub struct Debugger<'a> {
+    console: Console<'a>,
+    breakpoints: BTreeSet<usize>,
+    watch_acc: bool,
+    halted: Option<RunResult>,
+}
+
+enum Stop {
+    Halted(RunResult),
+    Breakpoint,
+    AccChanged(i32),
+    Loop,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(insts: &'a [Instruction]) -> Self {
+        Debugger {
+            console: Console::new(insts).with_trace(),
+            breakpoints: BTreeSet::new(),
+            watch_acc: false,
+            halted: None,
+        }
+    }
+
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            let words = line.split_whitespace().collect::<Vec<&str>>();
+            if words.is_empty() {
+                continue;
+            }
+
+            match words[0] {
+                "quit" | "q" => break,
+                "step" | "s" => {
+                    let count = words.get(1).and_then(|n| n.parse().ok()).unwrap_or(1);
+                    self.step(count, &mut output)?;
+                }
+                "continue" | "c" => self.resume(&mut output)?,
+                "break" | "b" => match words.get(1).and_then(|pc| pc.parse().ok()) {
+                    Some(pc) => {
+                        self.breakpoints.insert(pc);
+                        writeln!(output, "Breakpoint set at {}", pc)?;
+                    }
+                    None => writeln!(output, "Usage: break <pc>")?,
+                },
+                "delete" | "d" => match words.get(1).and_then(|pc| pc.parse().ok()) {
+                    Some(pc) => {
+                        self.breakpoints.remove(&pc);
+                        writeln!(output, "Breakpoint removed at {}", pc)?;
+                    }
+                    None => writeln!(output, "Usage: delete <pc>")?,
+                },
+                "watch" | "w" => {
+                    self.watch_acc = !self.watch_acc;
+                    let state = if self.watch_acc { "on" } else { "off" };
+                    writeln!(output, "Watching the accumulator is {}", state)?;
+                }
+                "history" | "h" => {
+                    let count = words.get(1).and_then(|n| n.parse().ok()).unwrap_or(10);
+                    self.history(count, &mut output)?;
+                }
+                "patch" => self.patch(&words[1..], &mut output)?,
+                "print" | "p" => self.print_state(&mut output)?,
+                cmd => writeln!(output, "Unknown command {}", cmd)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn step<W: Write>(&mut self, count: usize, output: &mut W) -> io::Result<()> {
+        for _ in 0..count {
+            if let Some(stop) = self.step_once() {
+                return self.report(stop, output);
+            }
+        }
+        self.print_state(output)
+    }
+
+    fn resume<W: Write>(&mut self, output: &mut W) -> io::Result<()> {
+        let acc = self.console.acc();
+        loop {
+            if let Some(stop) = self.step_once() {
+                return self.report(stop, output);
+            }
+            if self.breakpoints.contains(&self.console.pc()) {
+                return self.report(Stop::Breakpoint, output);
+            }
+            if self.watch_acc && self.console.acc() != acc {
+                return self.report(Stop::AccChanged(acc), output);
+            }
+            if self.console.is_looping() {
+                return self.report(Stop::Loop, output);
+            }
+        }
+    }
+
+    fn step_once(&mut self) -> Option<Stop> {
+        if let Some(result) = &self.halted {
+            return Some(Stop::Halted(result.clone()));
+        }
+
+        let result = self.console.step()?;
+        self.halted = Some(result.clone());
+        Some(Stop::Halted(result))
+    }
+
+    fn report<W: Write>(&self, stop: Stop, output: &mut W) -> io::Result<()> {
+        match stop {
+            Stop::Halted(result) => writeln!(output, "Program halted: {:?}", result)?,
+            Stop::Breakpoint => writeln!(output, "Hit breakpoint at {}", self.console.pc())?,
+            Stop::AccChanged(old) => writeln!(output, "Accumulator changed from {} to {}", old, self.console.acc())?,
+            Stop::Loop => writeln!(output, "About to execute {} a second time", self.console.pc())?,
+        }
+        self.print_state(output)
+    }
+
+    fn history<W: Write>(&self, count: usize, output: &mut W) -> io::Result<()> {
+        let trace = self.console.trace();
+        let start = trace.len().saturating_sub(count);
+        for entry in &trace[start..] {
+            writeln!(output, "{:>5}  {}  acc={}", entry.pc, self.console.instructions()[entry.pc], entry.registers[0])?;
+        }
+        Ok(())
+    }
+
+    fn patch<W: Write>(&mut self, args: &[&str], output: &mut W) -> io::Result<()> {
+        let pc = args.first().and_then(|pc| pc.parse::<usize>().ok());
+        let inst = args.get(1..).and_then(|rest| Instruction::parse(&rest.join(" ")).ok());
+        match (pc, inst) {
+            (Some(pc), Some(inst)) if pc < self.console.instructions().len() => {
+                writeln!(output, "Patched {} from {} to {}", pc, self.console.instructions()[pc], inst)?;
+                self.console.patch(pc, inst);
+            }
+            _ => writeln!(output, "Usage: patch <pc> <instruction>")?,
+        }
+        Ok(())
+    }
+
+    fn print_state<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        let pc = self.console.pc();
+        match self.console.instructions().get(pc) {
+            Some(inst) => writeln!(output, "pc={} acc={} next={}", pc, self.console.acc(), inst),
+            None => writeln!(output, "pc={} acc={} (end of program)", pc, self.console.acc()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::console::Instruction;
+    use crate::debugger::Debugger;
+
+    const PROGRAM: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    fn run_script(script: &str) -> String {
+        let insts = Instruction::from_lines(PROGRAM).unwrap();
+        let mut debugger = Debugger::new(&insts);
+        let mut output = Vec::new();
+        debugger.run(script.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn debugger_breakpoints_and_loops() {
+        let output = run_script("break 6\ncontinue\nstep 2\ncontinue\nhistory 3\n");
+        assert_eq!(output, "\
+Breakpoint set at 6
+Hit breakpoint at 6
+pc=6 acc=1 next=acc +1
+pc=3 acc=2 next=acc +3
+About to execute 1 a second time
+pc=1 acc=5 next=acc +1
+    7  jmp -4  acc=2
+    3  acc +3  acc=5
+    4  jmp -3  acc=5
+");
+    }
+
+    #[test]
+    fn debugger_patch_and_watch() {
+        let output = run_script("patch 7 nop -4\nwatch\ncontinue\nbreak 1\ndelete 1\ncontinue\ncontinue\ncontinue\nstep\n");
+        assert_eq!(output, "\
+Patched 7 from jmp -4 to nop -4
+Watching the accumulator is on
+Accumulator changed from 0 to 1
+pc=2 acc=1 next=jmp +4
+Breakpoint set at 1
+Breakpoint removed at 1
+Accumulator changed from 1 to 2
+pc=7 acc=2 next=nop -4
+Program halted: Terminated
+pc=9 acc=8 (end of program)
+Program halted: Terminated
+pc=9 acc=8 (end of program)
+Program halted: Terminated
+pc=9 acc=8 (end of program)
+");
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use crate::console::Instruction;
+
+pub fn format_program(insts: &[Instruction]) -> String {
+    insts.iter().map(|inst| inst.to_string()).collect::<Vec<String>>().join("\n")
+}
+
+fn in_bounds(insts: &[Instruction], target: isize) -> bool {
+    target >= 0 && target <= insts.len() as isize
+}
+
+pub fn reachable(insts: &[Instruction]) -> Vec<bool> {
+    let mut reachable = vec![false; insts.len()];
+    let mut pc = 0;
+    while pc < insts.len() && !reachable[pc] {
+        reachable[pc] = true;
+
+        let next = insts[pc].successor(pc);
+        if !in_bounds(insts, next) {
+            break;
+        }
+        pc = next as usize;
+    }
+    reachable
+}
+
+pub fn disassemble(insts: &[Instruction]) -> String {
+    let reachable = reachable(insts);
+    let mut jumped_from: Vec<Vec<usize>> = vec![Vec::new(); insts.len() + 1];
+    for (pc, inst) in insts.iter().enumerate() {
+        let target = inst.successor(pc);
+        if matches!(inst, Instruction::Jmp(_)) && in_bounds(insts, target) {
+            jumped_from[target as usize].push(pc);
+        }
+    }
+
+    let mut s = String::new();
+    for (pc, inst) in insts.iter().enumerate() {
+        let mut notes = Vec::new();
+        if let Instruction::Jmp(_) = inst {
+            let target = inst.successor(pc);
+            if target == insts.len() as isize {
+                notes.push("-> end".to_owned());
+            } else if in_bounds(insts, target) {
+                notes.push(format!("-> {}", target));
+            } else {
+                notes.push(format!("-> {} (out of bounds)", target));
+            }
+        }
+        if !jumped_from[pc].is_empty() {
+            let sources = jumped_from[pc].iter().map(|src| src.to_string()).collect::<Vec<String>>();
+            notes.push(format!("<- {}", sources.join(", ")));
+        }
+        if !reachable[pc] {
+            notes.push("unreachable".to_owned());
+        }
+
+        if notes.is_empty() {
+            writeln!(s, "{:>5}  {}", pc, inst).unwrap();
+        } else {
+            writeln!(s, "{:>5}  {:<10} ; {}", pc, inst.to_string(), notes.join("; ")).unwrap();
+        }
+    }
+    s
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BasicBlock {
+    pub start: usize,
+    pub end: usize,
+    pub successors: Vec<isize>,
+}
+
+pub fn basic_blocks(insts: &[Instruction]) -> Vec<BasicBlock> {
+    let mut leaders = BTreeSet::new();
+    leaders.insert(0);
+    for (pc, inst) in insts.iter().enumerate() {
+        if let Instruction::Jmp(_) = inst {
+            let target = inst.successor(pc);
+            if target >= 0 && (target as usize) < insts.len() {
+                leaders.insert(target as usize);
+            }
+            leaders.insert(pc + 1);
+        }
+    }
+    leaders.insert(insts.len());
+
+    let leaders = leaders.into_iter().collect::<Vec<usize>>();
+    leaders.windows(2).map(|bounds| {
+        let (start, end) = (bounds[0], bounds[1]);
+        let last = end - 1;
+        BasicBlock { start, end, successors: vec![insts[last].successor(last)] }
+    }).collect()
+}
+
+pub fn cfg_to_dot(insts: &[Instruction]) -> String {
+    let reachable = reachable(insts);
+    let mut s = String::from("digraph program {\n    node [shape=box, fontname=monospace];\n");
+    for block in basic_blocks(insts) {
+        let label = (block.start..block.end)
+            .map(|pc| format!("{}: {}\\l", pc, insts[pc]))
+            .collect::<String>();
+        let style = if reachable[block.start] { "" } else { ", style=dashed" };
+        writeln!(s, "    b{} [label=\"{}\"{}];", block.start, label, style).unwrap();
+
+        for target in block.successors {
+            if target == insts.len() as isize {
+                writeln!(s, "    b{} -> end;", block.start).unwrap();
+            } else if in_bounds(insts, target) {
+                writeln!(s, "    b{} -> b{};", block.start, target).unwrap();
+            } else {
+                writeln!(s, "    b{} -> out_of_bounds;", block.start).unwrap();
+            }
+        }
+    }
+    s.push_str("}\n");
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::console::Instruction;
+    use crate::disasm::{BasicBlock, basic_blocks, cfg_to_dot, disassemble, format_program, reachable};
+
+    const EXAMPLE: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    #[test]
+    fn formats_programs_back_to_source() {
+        for program in [EXAMPLE, "jmp -7\nacc +0\nnop -12", ""] {
+            assert_eq!(format_program(&Instruction::from_lines(program).unwrap()), program);
+        }
+    }
+
+    #[test]
+    fn splits_example_into_blocks() {
+        let insts = Instruction::from_lines(EXAMPLE).unwrap();
+        let block = |start, end, successor| BasicBlock { start, end, successors: vec![successor] };
+        assert_eq!(basic_blocks(&insts), vec![
+            block(0, 1, 1),
+            block(1, 3, 6),
+            block(3, 5, 1),
+            block(5, 6, 6),
+            block(6, 8, 3),
+            block(8, 9, 9),
+        ]);
+        assert_eq!(reachable(&insts), vec![true, true, true, true, true, false, true, true, false]);
+    }
+
+    #[test]
+    fn disassembles_example() {
+        let insts = Instruction::from_lines(EXAMPLE).unwrap();
+        assert_eq!(disassemble(&insts), concat!(
+            "    0  nop +0\n",
+            "    1  acc +1     ; <- 4\n",
+            "    2  jmp +4     ; -> 6\n",
+            "    3  acc +3     ; <- 7\n",
+            "    4  jmp -3     ; -> 1\n",
+            "    5  acc -99    ; unreachable\n",
+            "    6  acc +1     ; <- 2\n",
+            "    7  jmp -4     ; -> 3\n",
+            "    8  acc +6     ; unreachable\n",
+        ));
+
+        let insts = Instruction::from_lines("jmp +2\njmp -5").unwrap();
+        assert_eq!(disassemble(&insts), concat!(
+            "    0  jmp +2     ; -> end\n",
+            "    1  jmp -5     ; -> -4 (out of bounds); unreachable\n",
+        ));
+    }
+
+    #[test]
+    fn draws_control_flow_graph() {
+        let insts = Instruction::from_lines("nop +0\njmp +2\njmp -5").unwrap();
+        assert_eq!(cfg_to_dot(&insts), concat!(
+            "digraph program {\n",
+            "    node [shape=box, fontname=monospace];\n",
+            "    b0 [label=\"0: nop +0\\l1: jmp +2\\l\"];\n",
+            "    b0 -> end;\n",
+            "    b2 [label=\"2: jmp -5\\l\", style=dashed];\n",
+            "    b2 -> out_of_bounds;\n",
+            "}\n",
+        ));
+    }
+}
@@ -1,8 +1,13 @@
 mod console;
+mod debugger;
+mod disasm;
 
 use std::env;
 use std::fs;
-use crate::console::{Instruction, Console, RunResult};
+use std::io;
+use crate::console::{Instruction, Console, RunResult, repair, repair_candidates};
+use crate::debugger::Debugger;
+use crate::disasm::{cfg_to_dot, disassemble, format_program};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -15,41 +20,72 @@ fn main() {
     let instructions = Instruction::from_lines(&contents)
         .unwrap();
 
-    part1(&instructions);
+    let step_limit = args[2..].iter()
+        .find_map(|arg| arg.strip_prefix("--step-limit="))
+        .map(|n| n.parse::<usize>().expect("Could not parse step limit"));
+    let trace = args[2..].iter().any(|arg| arg == "--trace");
+
+    part1(&instructions, step_limit, trace);
     part2(&instructions);
+
+    if args[2..].iter().any(|arg| arg == "--candidates") {
+        print_candidates(&instructions);
+    }
+    if args[2..].iter().any(|arg| arg == "--disasm") {
+        print!("{}", disassemble(&instructions));
+    }
+    if args[2..].iter().any(|arg| arg == "--format") {
+        println!("{}", format_program(&instructions));
+    }
+    if args[2..].iter().any(|arg| arg == "--cfg") {
+        fs::write("day8.dot", cfg_to_dot(&instructions))
+            .expect("Something went wrong writing the DOT file");
+        println!("Wrote the control-flow graph to day8.dot");
+    }
+    if args[2..].iter().any(|arg| arg == "--debug") {
+        let stdin = io::stdin();
+        Debugger::new(&instructions).run(stdin.lock(), io::stdout())
+            .expect("Something went wrong running the debugger");
+    }
 }
 
-fn part1(insts: &Vec<Instruction>) {
+fn part1(insts: &[Instruction], step_limit: Option<usize>, trace: bool) {
     let mut console = Console::new(insts);
-    console.run();
-    println!("The value in the accumulator before looping is {}", console.acc());
+    if let Some(limit) = step_limit {
+        console = console.with_step_limit(limit);
+    }
+    if trace {
+        console = console.with_trace();
+    }
+
+    match console.run() {
+        RunResult::LoopDetected { pc } => {
+            println!("The value in the accumulator before looping back to {} is {}", pc, console.acc());
+        }
+        result => {
+            println!("The program stopped with {:?} and accumulator value {}", result, console.acc());
+        }
+    }
+
+    for entry in console.trace() {
+        println!("{:>5}  {}  {:?}", entry.pc, insts[entry.pc], entry.registers);
+    }
 }
 
-fn part2(insts: &Vec<Instruction>) {
-    let (i, acc) = (0..insts.len())
-        .filter(|i| {
-            match &insts[*i] {
-                Instruction::Acc(_) => false,
-                _ => true,
-            }
-        })
-        .map(|i| {
-            let mut new_insts = insts.clone();
-            new_insts[i] = match &insts[i] {
-                Instruction::Nop(n) => Instruction::Jmp(*n),
-                Instruction::Jmp(n) => Instruction::Nop(*n),
-                _ => panic!("Should not have reached this if the instruction wasn't a jmp or nop"),
-            };
-            (i, new_insts)
-        })
-        .find_map(|(i, insts)| {
-            let mut console = Console::new(&insts);
-            match console.run() {
-                RunResult::Terminated => Some((i, console.acc())),
-                RunResult::InfiniteLoop => None,
-            }
-        })
+fn part2(insts: &[Instruction]) {
+    if Console::new(insts).run() == RunResult::Terminated {
+        println!("The program already terminates without any changes");
+        return;
+    }
+
+    let fix = repair(insts)
         .expect("Did not find a change in the instructions that resulted in termination");
 
-    println!("Swapping the instruction at index {} allowed the program to terminate with accumulator value {}", i, acc);
+    println!("Swapping the instruction at index {} allowed the program to terminate with accumulator value {}", fix.pc, fix.acc);
+}
+
+fn print_candidates(insts: &[Instruction]) {
+    for candidate in repair_candidates(insts) {
+        println!("Changing index {} to {} terminates with accumulator value {}", candidate.pc, candidate.replacement, candidate.acc);
+    }
 }
\ No newline at end of file
@@ -1,7 +1,15 @@
+mod ranges;
+mod xmas;
+
 use std::env;
+use std::fmt::Display;
 use std::fs;
+use std::hash::Hash;
 use std::num::ParseIntError;
-use nom::lib::std::collections::VecDeque;
+use std::ops::Add;
+use std::str::FromStr;
+use crate::ranges::{ranges_summing_to, ranges_summing_to_signed};
+use crate::xmas::Xmas;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -11,56 +19,62 @@ fn main() {
 
     let contents = fs::read_to_string(input_path)
         .expect("Something went wrong reading the file");
-    let nums = contents.lines().map(|s| s.parse::<u64>()).collect::<Result<Vec<u64>, ParseIntError>>()
-        .expect("Could not parse lines as numbers");
 
-    part1(&nums);
-    part2(&nums);
-}
+    let window = args.get(2)
+        .filter(|arg| !arg.starts_with("--"))
+        .map(|n| n.parse::<usize>().expect("Could not parse the preamble size"))
+        .unwrap_or(25);
 
-fn part1(nums: &Vec<u64>) {
-    println!("The number {} does not equal the sum of two of the 25 preceding numbers.", target_number(nums));
+    if args[2..].iter().any(|arg| arg == "--signed") {
+        let nums = parse_numbers::<i64>(&contents);
+        if let Some(target) = part1(&Xmas::new(&nums, window), window) {
+            part2_signed(&nums, target);
+        }
+    } else {
+        let nums = parse_numbers::<u64>(&contents);
+        if let Some(target) = part1(&Xmas::new(&nums, window), window) {
+            part2(&nums, target);
+        }
+    }
 }
 
-fn part2(nums: &Vec<u64>) {
-    let mut rolling_nums: VecDeque<u64> = VecDeque::new();
-
-    let target = target_number(nums);
-
-    for n in nums {
-        rolling_nums.push_back(*n);
+fn parse_numbers<T: FromStr<Err = ParseIntError>>(contents: &str) -> Vec<T> {
+    contents.lines().map(|s| s.parse::<T>()).collect::<Result<Vec<T>, ParseIntError>>()
+        .expect("Could not parse lines as numbers")
+}
 
-        while rolling_nums.iter().sum::<u64>() > target {
-            rolling_nums.pop_front();
+fn part1<T: Copy + Eq + Hash + Add<Output = T> + Display>(xmas: &Xmas<T>, window: usize) -> Option<T> {
+    let target = match xmas.first_invalid() {
+        Some(target) => target,
+        None => {
+            println!("Every number is the sum of two of the {} preceding numbers.", window);
+            return None;
         }
+    };
 
-        if rolling_nums.iter().sum::<u64>() == target {
-            let min = rolling_nums.iter().min().unwrap();
-            let max = rolling_nums.iter().max().unwrap();
-            println!("The encryption weakness is {} + {} = {}", min, max, *min + *max);
-            return;
-        }
-    }
+    println!("The number {} does not equal the sum of two of the {} preceding numbers.", target, window);
 
-    panic!("Oh no! We couldn't find the encryption weakness");
+    let invalid_count = xmas.invalid_numbers().count();
+    if invalid_count > 1 {
+        println!("There are {} such numbers in total.", invalid_count);
+    }
+    Some(target)
 }
 
-fn target_number(nums: &Vec<u64>) -> u64 {
-    for i in 25..nums.len() {
-        let num = &nums[i];
-        let previous_nums = &nums[i-25..i];
+fn part2(nums: &[u64], target: u64) {
+    let ranges = ranges_summing_to(nums, target);
+    let first = ranges.first()
+        .expect("Oh no! We couldn't find the encryption weakness");
 
-        if !sums_of_pairs(previous_nums).iter().any(|n| n == num) {
-            return *num;
-        }
+    println!("The encryption weakness is {} + {} = {}", first.min, first.max, first.weakness());
+    for other in &ranges[1..] {
+        println!("The numbers at {:?} also sum to {}, with weakness {}", other.range, target, other.weakness());
     }
-
-    panic!("Oh no! We didn't find a number that matched our conditions");
 }
 
-fn sums_of_pairs(nums: &[u64]) -> Vec<u64> {
-    (&nums[..nums.len()-1]).iter().enumerate().flat_map(|(i, m)| {
-        let m = *m;
-        (&nums[i+1..nums.len()]).iter().map(move |n| *n + m)
-    }).collect()
-}
\ No newline at end of file
+fn part2_signed(nums: &[i64], target: i64) {
+    let ranges = ranges_summing_to_signed(nums, target);
+    for range in &ranges {
+        println!("The numbers at {:?} sum to {}, with weakness {} + {} = {}", range.range, target, range.min, range.max, range.weakness());
+    }
+}
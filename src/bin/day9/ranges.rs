@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::ops::{Add, Range};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ContiguousRange<T> {
+    pub range: Range<usize>,
+    pub min: T,
+    pub max: T,
+}
+
+impl<T: Copy + Ord + Add<Output = T>> ContiguousRange<T> {
+    fn new(nums: &[T], range: Range<usize>) -> Self {
+        let slice = &nums[range.clone()];
+        let min = *slice.iter().min().unwrap();
+        let max = *slice.iter().max().unwrap();
+        ContiguousRange { range, min, max }
+    }
+
+    pub fn weakness(&self) -> T {
+        self.min + self.max
+    }
+}
+
+// Every range of at least two numbers summing to target, using two pointers. Only works because
+// the numbers are non-negative: growing the window never shrinks its sum.
+pub fn ranges_summing_to(nums: &[u64], target: u64) -> Vec<ContiguousRange<u64>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut sum = 0;
+
+    for end in 0..nums.len() {
+        sum += nums[end];
+        while sum > target {
+            sum -= nums[start];
+            start += 1;
+        }
+
+        // zeros at the front of the window give more ranges with the same sum
+        let mut k = start;
+        let mut remaining = sum;
+        while k <= end && remaining == target {
+            if end + 1 - k >= 2 {
+                ranges.push(ContiguousRange::new(nums, k..end + 1));
+            }
+            remaining -= nums[k];
+            k += 1;
+        }
+    }
+
+    ranges
+}
+
+// Every range of at least two numbers summing to target, for input that may include negative
+// numbers, by looking up earlier prefix sums that differ from the current one by the target.
+pub fn ranges_summing_to_signed(nums: &[i64], target: i64) -> Vec<ContiguousRange<i64>> {
+    let mut prefix_sums = Vec::with_capacity(nums.len() + 1);
+    prefix_sums.push(0);
+    for n in nums {
+        prefix_sums.push(prefix_sums[prefix_sums.len() - 1] + n);
+    }
+
+    let mut ranges = Vec::new();
+    let mut seen: HashMap<i64, Vec<usize>> = HashMap::new();
+    for end in 2..prefix_sums.len() {
+        seen.entry(prefix_sums[end - 2]).or_default().push(end - 2);
+
+        if let Some(starts) = seen.get(&(prefix_sums[end] - target)) {
+            for start in starts {
+                ranges.push(ContiguousRange::new(nums, *start..end));
+            }
+        }
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ranges::{ContiguousRange, ranges_summing_to, ranges_summing_to_signed};
+
+    const EXAMPLE: [u64; 20] = [35, 20, 15, 25, 47, 40, 62, 55, 65, 95, 102, 117, 150, 182, 127, 219, 299, 277, 309, 576];
+
+    fn brute_force_ranges(nums: &[i64], target: i64) -> Vec<(usize, usize)> {
+        (0..=nums.len())
+            .flat_map(|end| (0..end.saturating_sub(1)).map(move |start| (start, end)))
+            .filter(|(start, end)| nums[*start..*end].iter().sum::<i64>() == target)
+            .collect()
+    }
+
+    fn bounds<T>(ranges: &[ContiguousRange<T>]) -> Vec<(usize, usize)> {
+        ranges.iter().map(|r| (r.range.start, r.range.end)).collect()
+    }
+
+    #[test]
+    fn finds_example_weakness() {
+        let ranges = ranges_summing_to(&EXAMPLE, 127);
+        assert_eq!(ranges, vec![ContiguousRange { range: 2..6, min: 15, max: 47 }]);
+        assert_eq!(ranges[0].weakness(), 62);
+
+        let signed = EXAMPLE.iter().map(|n| *n as i64).collect::<Vec<i64>>();
+        assert_eq!(ranges_summing_to_signed(&signed, 127), vec![ContiguousRange { range: 2..6, min: 15, max: 47 }]);
+    }
+
+    #[test]
+    fn runs_of_zeros() {
+        assert_eq!(bounds(&ranges_summing_to(&[0, 0, 0], 0)), vec![(0, 2), (0, 3), (1, 3)]);
+        assert_eq!(bounds(&ranges_summing_to(&[5, 0, 0, 5], 5)), vec![(0, 2), (0, 3), (1, 4), (2, 4)]);
+        assert_eq!(bounds(&ranges_summing_to_signed(&[5, 0, 0, 5], 5)), vec![(0, 2), (0, 3), (1, 4), (2, 4)]);
+    }
+
+    #[test]
+    fn ranges_have_at_least_two_numbers() {
+        assert_eq!(bounds(&ranges_summing_to(&[1, 2], 3)), vec![(0, 2)]);
+        assert!(ranges_summing_to(&[1, 2], 1).is_empty());
+        assert!(ranges_summing_to(&[3], 3).is_empty());
+        assert!(ranges_summing_to_signed(&[1, 2], 2).is_empty());
+        assert!(ranges_summing_to_signed(&[], 0).is_empty());
+    }
+
+    #[test]
+    fn negative_numbers() {
+        let ranges = ranges_summing_to_signed(&[-5, 10, -1, 1], 5);
+        assert_eq!(ranges, vec![
+            ContiguousRange { range: 0..2, min: -5, max: 10 },
+            ContiguousRange { range: 0..4, min: -5, max: 10 },
+        ]);
+        assert_eq!(ranges[1].weakness(), 5);
+
+        let nums = [1, -3, 4, 2, 0, -7, 8, -1, 1, 3];
+        for target in -8..=8 {
+            assert_eq!(bounds(&ranges_summing_to_signed(&nums, target)), brute_force_ranges(&nums, target));
+        }
+    }
+
+    #[test]
+    fn both_searches_agree_on_non_negative_input() {
+        let inputs: [&[u64]; 4] = [&EXAMPLE, &[0, 1, 0, 2, 0, 0, 3, 1], &[4, 4, 4, 4], &[7, 0, 0, 0, 7]];
+        for nums in inputs.iter() {
+            let signed = nums.iter().map(|n| *n as i64).collect::<Vec<i64>>();
+            for target in 0..=30 {
+                let ranges = ranges_summing_to(nums, target);
+                assert_eq!(bounds(&ranges), bounds(&ranges_summing_to_signed(&signed, target as i64)));
+                assert_eq!(bounds(&ranges), brute_force_ranges(&signed, target as i64));
+            }
+        }
+    }
+}
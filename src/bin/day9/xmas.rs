@@ -0,0 +1,147 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+pub struct Xmas<'a, T> {
+    nums: &'a [T],
+    window: usize,
+}
+
+impl<'a, T: Copy + Eq + Hash + Add<Output = T>> Xmas<'a, T> {
+    pub fn new(nums: &'a [T], window: usize) -> Self {
+        Xmas { nums, window }
+    }
+
+    pub fn invalid_numbers(&self) -> InvalidNumbers<'a, T> {
+        let preamble = &self.nums[..self.window.min(self.nums.len())];
+        let mut pair_sums = PairSums::new();
+        for n in preamble {
+            pair_sums.push(*n);
+        }
+
+        InvalidNumbers {
+            nums: self.nums,
+            pos: preamble.len(),
+            pair_sums,
+        }
+    }
+
+    pub fn first_invalid(&self) -> Option<T> {
+        self.invalid_numbers().next().map(|(_, n)| n)
+    }
+}
+
+pub struct InvalidNumbers<'a, T> {
+    nums: &'a [T],
+    pos: usize,
+    pair_sums: PairSums<T>,
+}
+
+impl<'a, T: Copy + Eq + Hash + Add<Output = T>> Iterator for InvalidNumbers<'a, T> {
+    type Item = (usize, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.nums.len() {
+            let i = self.pos;
+            let n = self.nums[i];
+            let valid = self.pair_sums.contains(n);
+
+            self.pair_sums.pop();
+            self.pair_sums.push(n);
+            self.pos += 1;
+
+            if !valid {
+                return Some((i, n));
+            }
+        }
+
+        None
+    }
+}
+
+// Keeps a count of every sum of two different numbers in the window, so sliding the window
+// along only touches the pairs involving the numbers entering and leaving it.
+struct PairSums<T> {
+    window: VecDeque<T>,
+    sums: HashMap<T, usize>,
+}
+
+impl<T: Copy + Eq + Hash + Add<Output = T>> PairSums<T> {
+    fn new() -> Self {
+        PairSums { window: VecDeque::new(), sums: HashMap::new() }
+    }
+
+    fn contains(&self, n: T) -> bool {
+        self.sums.contains_key(&n)
+    }
+
+    fn push(&mut self, n: T) {
+        for m in &self.window {
+            *self.sums.entry(n + *m).or_insert(0) += 1;
+        }
+        self.window.push_back(n);
+    }
+
+    fn pop(&mut self) {
+        if let Some(n) = self.window.pop_front() {
+            for m in &self.window {
+                let sum = n + *m;
+                let count = self.sums.get_mut(&sum).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    self.sums.remove(&sum);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::xmas::Xmas;
+
+    const EXAMPLE: [u64; 20] = [35, 20, 15, 25, 47, 40, 62, 55, 65, 95, 102, 117, 150, 182, 127, 219, 299, 277, 309, 576];
+
+    fn brute_force_invalid(nums: &[u64], window: usize) -> Vec<(usize, u64)> {
+        (window..nums.len())
+            .filter(|i| {
+                let preceding = &nums[i - window..*i];
+                !(0..window).any(|a| (a + 1..window).any(|b| preceding[a] + preceding[b] == nums[*i]))
+            })
+            .map(|i| (i, nums[i]))
+            .collect()
+    }
+
+    #[test]
+    fn finds_example_weakness() {
+        let xmas = Xmas::new(&EXAMPLE, 5);
+        assert_eq!(xmas.first_invalid(), Some(127));
+        assert_eq!(xmas.invalid_numbers().collect::<Vec<(usize, u64)>>(), vec![(14, 127)]);
+    }
+
+    #[test]
+    fn collects_every_invalid_number() {
+        let xmas = Xmas::new(&EXAMPLE, 3);
+        assert_eq!(xmas.invalid_numbers().map(|(_, n)| n).collect::<Vec<u64>>(),
+                   vec![25, 47, 62, 55, 65, 95, 102, 117, 150, 182, 127, 219, 299, 277, 309]);
+
+        for window in 1..=8 {
+            let xmas = Xmas::new(&EXAMPLE, window);
+            assert_eq!(xmas.invalid_numbers().collect::<Vec<(usize, u64)>>(), brute_force_invalid(&EXAMPLE, window));
+        }
+
+        // equal numbers in different positions still make a pair
+        let xmas = Xmas::new(&[1, 1, 2, 2], 2);
+        assert_eq!(xmas.invalid_numbers().collect::<Vec<(usize, u64)>>(), vec![(3, 2)]);
+
+        let xmas = Xmas::new(&[-1, 3, 2, 5, -4, 9], 2);
+        assert_eq!(xmas.invalid_numbers().collect::<Vec<(usize, i64)>>(), vec![(4, -4), (5, 9)]);
+    }
+
+    #[test]
+    fn short_input_has_no_invalid_numbers() {
+        assert_eq!(Xmas::new(&EXAMPLE[..4], 5).first_invalid(), None);
+        assert_eq!(Xmas::new(&EXAMPLE[..5], 5).first_invalid(), None);
+        assert_eq!(Xmas::<u64>::new(&[], 25).first_invalid(), None);
+    }
+}